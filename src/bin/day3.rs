@@ -1,32 +1,75 @@
 extern crate aoc_2018;
 
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::ops::Range;
 use std::str::FromStr;
 
 use aoc_2018::file_lines;
 
 fn main() {
-    let claims: Vec<Claim> = read_claims();
-    let cloth = claim_cloth(&claims);
+    let (cloth, claims) = claim_cloth(read_edits());
 
     println!("overlap area: {}", cloth.overlap_area());
+    let claim = non_overlapping(&cloth, &claims);
+    debug_assert!(claims
+        .iter()
+        .all(|other| other.id == claim.id || claim.intersection(other).is_none()));
     println!(
-        "non overlapping claim: {}",
-        non_overlapping(&cloth, &claims).id
+        "non overlapping claim: {} ({}x{}, area {})",
+        claim.id,
+        claim.width(),
+        claim.height(),
+        claim.area()
     );
+
+    if let Some(filename) = env::args().nth(2) {
+        let file = File::create(&filename).expect("could not create image file");
+        cloth
+            .write_ppm(&mut BufWriter::new(file), Some(claim))
+            .expect("error writing image file");
+        println!("heatmap written to {}", filename);
+    }
 }
 
-fn read_claims() -> Vec<Claim> {
-    file_lines().map(|s| s.parse::<Claim>().unwrap()).collect()
+fn read_edits() -> Vec<Edit> {
+    file_lines().map(|s| s.parse::<Edit>().unwrap()).collect()
 }
 
-fn claim_cloth(claims: &Vec<Claim>) -> Cloth {
-    let width: usize = claims.iter().map(|r| r.right as usize).max().unwrap();
-    let height: usize = claims.iter().map(|r| r.bottom as usize).max().unwrap();
+fn claim_cloth(edits: Vec<Edit>) -> (Cloth, Vec<Claim>) {
+    let added = || {
+        edits.iter().filter_map(|edit| match edit {
+            Edit::Claim(claim) => Some(claim),
+            Edit::Unclaim(_) => None,
+        })
+    };
+    let width: usize = added().map(|r| r.right as usize).max().unwrap();
+    let height: usize = added().map(|r| r.bottom as usize).max().unwrap();
     let mut cloth: Cloth = Cloth::new(width, height);
-    for claim in claims {
-        cloth.claim(claim);
+    let mut claims: Vec<Claim> = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::Claim(claim) => {
+                if claims.iter().any(|active| active.id == claim.id) {
+                    panic!("claim #{} is already active", claim.id);
+                }
+                cloth.claim(&claim);
+                claims.push(claim);
+            }
+            Edit::Unclaim(id) => {
+                let index = claims
+                    .iter()
+                    .position(|claim| claim.id == id)
+                    .unwrap_or_else(|| panic!("no active claim #{} to withdraw", id));
+                cloth.unclaim(&claims.remove(index));
+            }
+        }
     }
-    cloth
+    (cloth, claims)
 }
 
 fn non_overlapping<'a>(cloth: &Cloth, claims: &'a [Claim]) -> &'a Claim {
@@ -41,6 +84,9 @@ struct Cloth {
     width: usize,
     height: usize,
     coverage: Vec<Vec<u32>>,
+    owners: Vec<Vec<u32>>,
+    shared: HashMap<u32, u32>,
+    overlap_area: usize,
 }
 
 impl Cloth {
@@ -48,68 +94,178 @@ impl Cloth {
         Self {
             width,
             height,
-            coverage: vec![vec![0 as u32; width]; height],
+            coverage: vec![vec![0; width]; height],
+            owners: vec![vec![0; width]; height],
+            shared: HashMap::new(),
+            overlap_area: 0,
         }
     }
 
     fn claim(&mut self, claim: &Claim) {
-        for x in claim.left..claim.right {
-            for y in claim.top..claim.bottom {
-                self.coverage[y as usize][x as usize] += 1;
+        let mut shared = 0;
+        for x in claim.left as usize..claim.right as usize {
+            for y in claim.top as usize..claim.bottom as usize {
+                let count = self.coverage[y][x];
+                if count == 1 {
+                    self.overlap_area += 1;
+                    *self.shared.entry(self.owners[y][x]).or_insert(0) += 1;
+                }
+                if count > 0 {
+                    shared += 1;
+                }
+                self.coverage[y][x] += 1;
+                self.owners[y][x] ^= claim.id;
+            }
+        }
+        self.shared.insert(claim.id, shared);
+    }
+
+    fn unclaim(&mut self, claim: &Claim) {
+        for x in claim.left as usize..claim.right as usize {
+            for y in claim.top as usize..claim.bottom as usize {
+                self.coverage[y][x] -= 1;
+                self.owners[y][x] ^= claim.id;
+                if self.coverage[y][x] == 1 {
+                    self.overlap_area -= 1;
+                    *self.shared.get_mut(&self.owners[y][x]).unwrap() -= 1;
+                }
             }
         }
+        self.shared.remove(&claim.id);
     }
 
     fn overlap_area(&self) -> usize {
-        self.coverage
-            .iter()
-            .map(|ref v| v.iter().filter(|&&count| count > 1).count())
-            .sum()
+        self.overlap_area
     }
 
     fn has_no_overlap(&self, claim: &Claim) -> bool {
-        for x in claim.left..claim.right {
-            for y in claim.top..claim.bottom {
-                if self.coverage[y as usize][x as usize] != 1 {
-                    return false;
-                }
+        self.shared.get(&claim.id) == Some(&0)
+    }
+
+    fn write_ppm<W: Write>(&self, out: &mut W, highlight: Option<&Claim>) -> io::Result<()> {
+        writeln!(out, "P6\n{} {}\n255", self.width, self.height)?;
+        for (y, row) in self.coverage.iter().enumerate() {
+            for (x, &count) in row.iter().enumerate() {
+                let highlighted = highlight.is_some_and(|claim| claim.contains(x, y));
+                out.write_all(&if highlighted {
+                    [0, 255, 0]
+                } else {
+                    heat_colour(count)
+                })?;
             }
         }
-        true
+        out.flush()
+    }
+}
+
+fn heat_colour(count: u32) -> [u8; 3] {
+    match count {
+        0 => [0, 0, 0],
+        1 => [64, 64, 160],
+        2 => [255, 200, 0],
+        3 => [255, 120, 0],
+        _ => [255, 0, 0],
+    }
+}
+
+enum Edit {
+    Claim(Claim),
+    Unclaim(u32),
+}
+
+impl FromStr for Edit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        match s.strip_prefix("-#") {
+            Some(id) => Ok(Edit::Unclaim(parse_field("id", id, s)?)),
+            None => Ok(Edit::Claim(s.parse()?)),
+        }
     }
 }
 
 #[derive(Debug)]
 struct Claim {
-    id: String,
+    id: u32,
     left: u32,
     top: u32,
     right: u32,
     bottom: u32,
 }
 
+impl Claim {
+    fn width(&self) -> u32 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> u32 {
+        self.bottom - self.top
+    }
+
+    fn area(&self) -> u64 {
+        u64::from(self.width()) * u64::from(self.height())
+    }
+
+    fn intersection(&self, other: &Claim) -> Option<(Range<u32>, Range<u32>)> {
+        let columns = self.left.max(other.left)..self.right.min(other.right);
+        let rows = self.top.max(other.top)..self.bottom.min(other.bottom);
+        if columns.is_empty() || rows.is_empty() {
+            None
+        } else {
+            Some((columns, rows))
+        }
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        (self.left as usize..self.right as usize).contains(&x)
+            && (self.top as usize..self.bottom as usize).contains(&y)
+    }
+}
+
 impl FromStr for Claim {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
-        let mut parts = s.splitn(2, " @ ");
-        match (parts.next(), parts.next()) {
-            (Some(id), Some(coordinates)) => {
-                let mut parts = coordinates
-                    .splitn(5, |c: char| !c.is_numeric())
-                    .filter_map(|s| s.parse::<u32>().ok());
-                match (parts.next(), parts.next(), parts.next(), parts.next()) {
-                    (Some(left), Some(top), Some(width), Some(height)) => Ok(Claim {
-                        id: id.to_string(),
-                        left,
-                        top,
-                        right: left + width,
-                        bottom: top + height,
-                    }),
-                    _ => Err(format!("Invalid coordinates: {}", coordinates)),
-                }
-            }
-            _ => Err(format!("Invalid claim: '{}'", s)),
-        }
+        let (id, rectangle) = s
+            .split_once(" @ ")
+            .ok_or_else(|| format!("Invalid claim, missing ' @ ': '{}'", s))?;
+        let (position, size) = rectangle
+            .split_once(": ")
+            .ok_or_else(|| format!("Invalid claim, missing ': ': '{}'", s))?;
+        let (left, top) = position
+            .split_once(',')
+            .ok_or_else(|| format!("Invalid position '{}' in claim '{}'", position, s))?;
+        let (width, height) = size
+            .split_once('x')
+            .ok_or_else(|| format!("Invalid size '{}' in claim '{}'", size, s))?;
+
+        let id = match id.strip_prefix('#') {
+            Some(id) => parse_field("id", id, s)?,
+            None => return Err(format!("Invalid id '{}' in claim '{}'", id, s)),
+        };
+        let left = parse_field("left", left, s)?;
+        let top = parse_field("top", top, s)?;
+        let width = parse_field("width", width, s)?;
+        let height = parse_field("height", height, s)?;
+        Ok(Claim {
+            id,
+            left,
+            top,
+            right: left
+                .checked_add(width)
+                .ok_or_else(|| format!("Claim exceeds the cloth horizontally: '{}'", s))?,
+            bottom: top
+                .checked_add(height)
+                .ok_or_else(|| format!("Claim exceeds the cloth vertically: '{}'", s))?,
+        })
+    }
+}
+
+fn parse_field(name: &str, value: &str, claim: &str) -> Result<u32, String> {
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid {} '{}' in claim '{}'", name, value, claim));
     }
+    value
+        .parse()
+        .map_err(|e| format!("Invalid {} '{}' in claim '{}': {}", name, value, claim, e))
 }
@@ -3,91 +3,749 @@ extern crate core;
 
 use aoc_2018::file_lines;
 use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::num::ParseIntError;
+use std::ops::Range;
+use std::process;
 use std::str::FromStr;
 
 fn main() {
-    let sleep_times = read_sleep_times();
-
-    let (&Guard(guard), histogram) = sleep_times.iter().max_by_key(|(_k, v)| v.sum()).unwrap();
-    let minute = u32::from(histogram.mode());
-    println!(
-        "Choosing guard {} and minute {}: {}",
-        guard,
-        minute,
-        guard * minute
+    let mut mode = Mode::Lenient;
+    let mut window = Window::MIDNIGHT_HOUR;
+    let mut schedule = None;
+    let mut report = None;
+    for arg in env::args().skip(2) {
+        match arg.as_str() {
+            "--strict" => mode = Mode::Strict,
+            "--schedule" => schedule = Some(None),
+            "--report" => report = Some((Format::Table, Column::Total)),
+            "--csv" => report = Some((Format::Csv, Column::Total)),
+            _ => {
+                if let Some(column) = arg.strip_prefix("--report=") {
+                    report = Some((
+                        Format::Table,
+                        column.parse().unwrap_or_else(|e| panic!("{}", e)),
+                    ));
+                } else if let Some(column) = arg.strip_prefix("--csv=") {
+                    report = Some((
+                        Format::Csv,
+                        column.parse().unwrap_or_else(|e| panic!("{}", e)),
+                    ));
+                } else if let Some(id) = arg.strip_prefix("--schedule=") {
+                    schedule = Some(Some(Guard(id.parse().expect("Invalid guard id"))));
+                } else if let Some(range) = arg.strip_prefix("--window=") {
+                    window = range.parse().unwrap_or_else(|error| panic!("{}", error));
+                } else {
+                    panic!("Unknown option '{}'", arg);
+                }
+            }
+        }
+    }
+    let Log {
+        sleep_times,
+        nights,
+        anomalies,
+    } = match read_log(mode, window) {
+        Ok(log) => log,
+        Err(anomaly) => {
+            eprintln!("Invalid log: {}", anomaly);
+            process::exit(1);
+        }
+    };
+    for anomaly in anomalies {
+        eprintln!("Ignoring {}", anomaly);
+    }
+    if let Some(guard) = schedule {
+        print!("{}", render_schedule(&nights, guard));
+    }
+    if let Some((format, column)) = report {
+        let mut statistics = guard_statistics(&sleep_times, &nights);
+        sort_statistics(&mut statistics, column);
+        print!(
+            "{}",
+            match format {
+                Format::Table => render_report(&statistics),
+                Format::Csv => render_csv(&statistics),
+            }
+        );
+    }
+
+    let strategies: [&dyn Strategy; 4] = [
+        &MostMinutesAsleep,
+        &MostFrequentMinute,
+        &MostNightsAsleep,
+        &LongestNap,
+    ];
+    for strategy in strategies.iter() {
+        match choose(*strategy, &sleep_times, &nights) {
+            Ok((Guard(guard), minute)) => println!(
+                "{}: Choosing guard {} and minute {}: {}",
+                strategy.name(),
+                guard,
+                minute,
+                i64::from(guard) * i64::from(minute)
+            ),
+            Err(tie) => println!("{}: {}", strategy.name(), tie),
+        }
+    }
+}
+
+struct GuardStatistics {
+    guard: Guard,
+    total_minutes: u32,
+    nights_on_duty: u32,
+    nights_asleep: u32,
+    mean_nap: f64,
+    longest_nap: u32,
+    sleepiest_minute: Option<Minute>,
+    frequency: u32,
+    sparkline: String,
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    Guard,
+    Total,
+    Nights,
+    Asleep,
+    MeanNap,
+    LongestNap,
+    Minute,
+    Frequency,
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        Ok(match s {
+            "guard" => Column::Guard,
+            "total" => Column::Total,
+            "nights" => Column::Nights,
+            "asleep" => Column::Asleep,
+            "mean-nap" => Column::MeanNap,
+            "longest-nap" => Column::LongestNap,
+            "minute" => Column::Minute,
+            "frequency" => Column::Frequency,
+            _ => return Err(format!("Unknown report column '{}'", s)),
+        })
+    }
+}
+
+enum Format {
+    Table,
+    Csv,
+}
+
+fn guard_statistics(
+    sleep_times: &HashMap<Guard, Histogram>,
+    nights: &[Night],
+) -> Vec<GuardStatistics> {
+    let mut guards: Vec<Guard> = nights.iter().map(|night| night.guard).collect();
+    guards.sort_unstable();
+    guards.dedup();
+    let window = nights
+        .first()
+        .map_or(Window::MIDNIGHT_HOUR, |night| night.window);
+    let empty = Histogram::new(window);
+    guards
+        .into_iter()
+        .map(|guard| {
+            let histogram = sleep_times.get(&guard).unwrap_or(&empty);
+            let guard_nights: Vec<&Night> =
+                nights.iter().filter(|night| night.guard == guard).collect();
+            let naps: Vec<u32> = guard_nights.iter().flat_map(|night| night.naps()).collect();
+            let frequency = histogram.max_freq();
+            GuardStatistics {
+                guard,
+                total_minutes: histogram.sum(),
+                nights_on_duty: guard_nights.len() as u32,
+                nights_asleep: guard_nights.iter().filter(|night| night.slept()).count() as u32,
+                mean_nap: if naps.is_empty() {
+                    0.0
+                } else {
+                    f64::from(naps.iter().sum::<u32>()) / naps.len() as f64
+                },
+                longest_nap: naps.iter().cloned().max().unwrap_or(0),
+                sleepiest_minute: if frequency > 0 {
+                    histogram.modes().first().cloned()
+                } else {
+                    None
+                },
+                frequency,
+                sparkline: histogram.sparkline(),
+            }
+        })
+        .collect()
+}
+
+fn sort_statistics(statistics: &mut [GuardStatistics], column: Column) {
+    statistics.sort_by(|a, b| match column {
+        Column::Guard => a.guard.cmp(&b.guard),
+        Column::Total => b.total_minutes.cmp(&a.total_minutes),
+        Column::Nights => b.nights_on_duty.cmp(&a.nights_on_duty),
+        Column::Asleep => b.nights_asleep.cmp(&a.nights_asleep),
+        Column::MeanNap => b.mean_nap.total_cmp(&a.mean_nap),
+        Column::LongestNap => b.longest_nap.cmp(&a.longest_nap),
+        Column::Minute => a.sleepiest_minute.cmp(&b.sleepiest_minute),
+        Column::Frequency => b.frequency.cmp(&a.frequency),
+    });
+}
+
+fn render_report(statistics: &[GuardStatistics]) -> String {
+    let mut output = format!(
+        "{:>6} {:>6} {:>6} {:>6} {:>8} {:>7} {:>6} {:>5}  {}\n",
+        "Guard", "Total", "Nights", "Asleep", "Mean nap", "Longest", "Minute", "Freq", "Histogram"
     );
-    let (&Guard(guard), histogram) = sleep_times
-        .iter()
-        .max_by_key(|(_k, v)| v.max_freq())
-        .unwrap();
-    let minute = u32::from(histogram.mode());
-    println!(
-        "Choosing guard {} and minute {}: {}",
-        guard,
-        minute,
-        guard * minute
+    for stats in statistics {
+        output.push_str(&format!(
+            "{:>6} {:>6} {:>6} {:>6} {:>8.1} {:>7} {:>6} {:>5}  {}\n",
+            format!("#{}", stats.guard.0),
+            stats.total_minutes,
+            stats.nights_on_duty,
+            stats.nights_asleep,
+            stats.mean_nap,
+            stats.longest_nap,
+            stats
+                .sleepiest_minute
+                .map_or_else(|| "-".to_string(), |minute| minute.to_string()),
+            stats.frequency,
+            stats.sparkline
+        ));
+    }
+    output
+}
+
+fn render_csv(statistics: &[GuardStatistics]) -> String {
+    let mut output = String::from(
+        "guard,total_minutes,nights_on_duty,nights_asleep,mean_nap,longest_nap,sleepiest_minute,frequency\n",
     );
+    for stats in statistics {
+        output.push_str(&format!(
+            "{},{},{},{},{:.2},{},{},{}\n",
+            stats.guard.0,
+            stats.total_minutes,
+            stats.nights_on_duty,
+            stats.nights_asleep,
+            stats.mean_nap,
+            stats.longest_nap,
+            stats
+                .sleepiest_minute
+                .map_or_else(String::new, |minute| minute.to_string()),
+            stats.frequency
+        ));
+    }
+    output
+}
+
+trait Strategy {
+    fn name(&self) -> &'static str;
+
+    fn score(&self, histogram: &Histogram, nights: &[&Night]) -> u32;
+}
+
+struct MostMinutesAsleep;
+
+impl Strategy for MostMinutesAsleep {
+    fn name(&self) -> &'static str {
+        "Most minutes asleep"
+    }
+
+    fn score(&self, histogram: &Histogram, _nights: &[&Night]) -> u32 {
+        histogram.sum()
+    }
+}
+
+struct MostFrequentMinute;
+
+impl Strategy for MostFrequentMinute {
+    fn name(&self) -> &'static str {
+        "Most frequently asleep on the same minute"
+    }
+
+    fn score(&self, histogram: &Histogram, _nights: &[&Night]) -> u32 {
+        histogram.max_freq()
+    }
+}
+
+struct MostNightsAsleep;
+
+impl Strategy for MostNightsAsleep {
+    fn name(&self) -> &'static str {
+        "Most distinct nights asleep"
+    }
+
+    fn score(&self, _histogram: &Histogram, nights: &[&Night]) -> u32 {
+        nights.iter().filter(|night| night.slept()).count() as u32
+    }
+}
+
+struct LongestNap;
+
+impl Strategy for LongestNap {
+    fn name(&self) -> &'static str {
+        "Longest single nap"
+    }
+
+    fn score(&self, _histogram: &Histogram, nights: &[&Night]) -> u32 {
+        nights
+            .iter()
+            .flat_map(|night| night.naps())
+            .max()
+            .unwrap_or(0)
+    }
 }
 
-fn read_sleep_times() -> HashMap<Guard, Histogram> {
-    let mut entries: Vec<LogEntry> = file_lines()
-        .filter_map(|line| LogEntry::from_str(line.as_str()).ok())
-        .collect();
-    entries.sort_unstable_by(|a, b| a.timestamp.cmp(&b.timestamp));
-    let mut result: HashMap<Guard, Histogram> = HashMap::new();
-    let mut guard: Guard = Guard(0);
-    let mut start: u8 = 0;
+enum Tie {
+    Nobody,
+    Guards(Vec<Guard>, u32),
+    Minutes(Guard, Vec<Minute>),
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tie::Nobody => write!(f, "no guard slept"),
+            Tie::Guards(guards, score) => {
+                let guards: Vec<String> =
+                    guards.iter().map(|guard| format!("#{}", guard.0)).collect();
+                write!(
+                    f,
+                    "tie between guards {} with score {}",
+                    guards.join(", "),
+                    score
+                )
+            }
+            Tie::Minutes(guard, minutes) => {
+                let minutes: Vec<String> = minutes.iter().map(Minute::to_string).collect();
+                write!(
+                    f,
+                    "guard #{} is equally often asleep on minutes {}",
+                    guard.0,
+                    minutes.join(", ")
+                )
+            }
+        }
+    }
+}
+
+fn choose(
+    strategy: &dyn Strategy,
+    sleep_times: &HashMap<Guard, Histogram>,
+    nights: &[Night],
+) -> Result<(Guard, Minute), Tie> {
+    let mut best: Vec<Guard> = Vec::new();
+    let mut best_score = 0;
+    for (&guard, histogram) in sleep_times {
+        let guard_nights: Vec<&Night> =
+            nights.iter().filter(|night| night.guard == guard).collect();
+        let score = strategy.score(histogram, &guard_nights);
+        if best.is_empty() || score > best_score {
+            best = vec![guard];
+            best_score = score;
+        } else if score == best_score {
+            best.push(guard);
+        }
+    }
+    if best.is_empty() {
+        return Err(Tie::Nobody);
+    }
+    if best.len() != 1 {
+        best.sort_unstable();
+        return Err(Tie::Guards(best, best_score));
+    }
+    let guard = best[0];
+    let minutes = sleep_times[&guard].modes();
+    if minutes.len() != 1 {
+        return Err(Tie::Minutes(guard, minutes));
+    }
+    Ok((guard, minutes[0]))
+}
+
+fn read_log(mode: Mode, window: Window) -> Result<Log, Anomaly> {
+    let mut processor = LogProcessor::new(mode, window);
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in file_lines() {
+        match LogEntry::from_str(line.as_str()) {
+            Ok(entry) => entries.push(entry),
+            Err(error) => processor.report(Anomaly::Unparsable(line, error))?,
+        }
+    }
+    entries.sort_unstable_by_key(|entry| entry.timestamp);
     for entry in entries {
+        processor.process(entry)?;
+    }
+    processor.finish()
+}
+
+fn render_schedule(nights: &[Night], guard: Option<Guard>) -> String {
+    let mut output = String::from("Date   ID     Minute\n");
+    let window = nights
+        .first()
+        .map_or(Window::MIDNIGHT_HOUR, |night| night.window);
+    for digits in &[|m: Minute| m / 10, |m: Minute| m % 10] {
+        output.push_str("              ");
+        output.extend(
+            window
+                .minutes()
+                .map(|minute| char::from(b'0' + digits(minute.rem_euclid(60)) as u8)),
+        );
+        output.push('\n');
+    }
+    for night in nights
+        .iter()
+        .filter(|night| guard.is_none_or(|guard| night.guard == guard))
+    {
+        output.push_str(&format!(
+            "{:02}-{:02}  #{:<5} ",
+            night.date.month, night.date.day, night.guard.0
+        ));
+        output.extend(
+            night
+                .asleep
+                .iter()
+                .map(|&asleep| if asleep { '#' } else { '.' }),
+        );
+        output.push('\n');
+    }
+    output
+}
+
+struct Log {
+    sleep_times: HashMap<Guard, Histogram>,
+    nights: Vec<Night>,
+    anomalies: Vec<Anomaly>,
+}
+
+struct Night {
+    date: Date,
+    guard: Guard,
+    window: Window,
+    asleep: Vec<bool>,
+}
+
+impl Night {
+    fn slept(&self) -> bool {
+        self.asleep.contains(&true)
+    }
+
+    fn naps(&self) -> impl Iterator<Item = u32> + '_ {
+        self.asleep
+            .split(|&asleep| !asleep)
+            .filter(|nap| !nap.is_empty())
+            .map(|nap| nap.len() as u32)
+    }
+}
+
+/// Minutes relative to midnight at the start of a shift's date.
+type Minute = i32;
+
+const MINUTES_PER_DAY: Minute = 24 * 60;
+
+#[derive(Clone, Copy)]
+struct Window {
+    start: Minute,
+    end: Minute,
+}
+
+impl Window {
+    const MIDNIGHT_HOUR: Window = Window { start: 0, end: 60 };
+
+    fn len(&self) -> usize {
+        (self.end - self.start) as usize
+    }
+
+    fn minutes(&self) -> Range<Minute> {
+        self.start..self.end
+    }
+
+    fn index(&self, minute: Minute) -> usize {
+        (minute - self.start) as usize
+    }
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| format!("Invalid window '{}', expected START..END", s))?;
+        let parse = |value: &str| {
+            value
+                .parse::<Minute>()
+                .map_err(|e| format!("Invalid window '{}': {}", s, e))
+        };
+        let window = Window {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if window.start >= window.end || window.end - window.start > MINUTES_PER_DAY {
+            return Err(format!(
+                "Invalid window '{}', must cover 1 to {} minutes",
+                s, MINUTES_PER_DAY
+            ));
+        }
+        Ok(window)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Strict,
+    Lenient,
+}
+
+enum Anomaly {
+    Unparsable(String, String),
+    NoGuardOnDuty(Timestamp),
+    OrphanWakeUp(Timestamp),
+    DoubleSleep(Timestamp),
+    UnclosedSleep(Timestamp),
+    SleepOutsideWindow(Timestamp),
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Anomaly::Unparsable(line, error) => write!(f, "unparsable line '{}': {}", line, error),
+            Anomaly::NoGuardOnDuty(time) => write!(f, "[{}] event before any shift began", time),
+            Anomaly::OrphanWakeUp(time) => write!(f, "[{}] wake-up without falling asleep", time),
+            Anomaly::DoubleSleep(time) => write!(f, "[{}] falls asleep while asleep", time),
+            Anomaly::UnclosedSleep(time) => {
+                write!(f, "[{}] sleep not closed before the shift ended", time)
+            }
+            Anomaly::SleepOutsideWindow(time) => {
+                write!(f, "[{}] sleep event outside the observed window", time)
+            }
+        }
+    }
+}
+
+struct LogProcessor {
+    mode: Mode,
+    window: Window,
+    guard: Option<Guard>,
+    asleep: Option<Asleep>,
+    sleep_times: HashMap<Guard, Histogram>,
+    nights: Vec<Night>,
+    anomalies: Vec<Anomaly>,
+}
+
+// A rejected fall-asleep entry is remembered so that its wake-up is not
+// reported as a second anomaly.
+enum Asleep {
+    Since(Timestamp),
+    Ignored,
+}
+
+impl LogProcessor {
+    fn new(mode: Mode, window: Window) -> Self {
+        LogProcessor {
+            mode,
+            window,
+            guard: None,
+            asleep: None,
+            sleep_times: HashMap::new(),
+            nights: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, anomaly: Anomaly) -> Result<(), Anomaly> {
+        match self.mode {
+            Mode::Strict => Err(anomaly),
+            Mode::Lenient => {
+                self.anomalies.push(anomaly);
+                Ok(())
+            }
+        }
+    }
+
+    fn process(&mut self, entry: LogEntry) -> Result<(), Anomaly> {
+        let timestamp = entry.timestamp;
         match entry.action {
             Action::BeginsShift(next_guard) => {
-                guard = next_guard;
+                if let Some(Asleep::Since(start)) = self.asleep.take() {
+                    self.report(Anomaly::UnclosedSleep(start))?;
+                }
+                self.guard = Some(next_guard);
+                self.nights.push(Night {
+                    date: timestamp.shift_date(),
+                    guard: next_guard,
+                    window: self.window,
+                    asleep: vec![false; self.window.len()],
+                });
+            }
+            Action::FallsAsleep => {
+                if self.guard.is_none() {
+                    self.report(Anomaly::NoGuardOnDuty(timestamp))?;
+                } else if !self
+                    .window
+                    .minutes()
+                    .contains(&self.shift_minute(&timestamp))
+                {
+                    self.report(Anomaly::SleepOutsideWindow(timestamp))?;
+                    self.asleep.get_or_insert(Asleep::Ignored);
+                } else if self.asleep.is_some() {
+                    self.report(Anomaly::DoubleSleep(timestamp))?;
+                } else {
+                    self.asleep = Some(Asleep::Since(timestamp));
+                }
             }
-            Action::FallsAsleep => start = entry.timestamp.minute,
-            Action::WakesUp => result
-                .entry(guard)
-                .or_insert_with(Histogram::new)
-                .add(start, entry.timestamp.minute),
+            Action::WakesUp => match (self.guard, self.asleep.take()) {
+                (None, _) => self.report(Anomaly::NoGuardOnDuty(timestamp))?,
+                (Some(_), None) => self.report(Anomaly::OrphanWakeUp(timestamp))?,
+                (Some(_), Some(Asleep::Ignored)) => {}
+                (Some(guard), Some(Asleep::Since(start))) => {
+                    let start = self.shift_minute(&start);
+                    let end = self.shift_minute(&timestamp);
+                    if end > self.window.end {
+                        self.report(Anomaly::SleepOutsideWindow(timestamp))?;
+                        return Ok(());
+                    }
+                    let window = self.window;
+                    self.sleep_times
+                        .entry(guard)
+                        .or_insert_with(|| Histogram::new(window))
+                        .add(start, end);
+                    let night = self.nights.last_mut().unwrap();
+                    for asleep in &mut night.asleep[window.index(start)..window.index(end)] {
+                        *asleep = true;
+                    }
+                }
+            },
         }
+        Ok(())
+    }
+
+    fn shift_minute(&self, timestamp: &Timestamp) -> Minute {
+        let night = self.nights.last().unwrap();
+        timestamp.minutes_since(night.date)
+    }
+
+    fn finish(mut self) -> Result<Log, Anomaly> {
+        if let Some(Asleep::Since(start)) = self.asleep.take() {
+            self.report(Anomaly::UnclosedSleep(start))?;
+        }
+        Ok(Log {
+            sleep_times: self.sleep_times,
+            nights: self.nights,
+            anomalies: self.anomalies,
+        })
     }
-    result
 }
 
-#[derive(Hash, Eq, PartialEq, Copy, Clone)]
+#[derive(Hash, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
 struct Guard(u32);
 
-struct Histogram([u32; 60]);
+struct Histogram {
+    window: Window,
+    counts: Vec<u32>,
+}
 
 impl Histogram {
-    fn new() -> Histogram {
-        Histogram([0; 60])
+    fn new(window: Window) -> Histogram {
+        Histogram {
+            window,
+            counts: vec![0; window.len()],
+        }
     }
 
-    fn add(&mut self, start: u8, end: u8) {
-        for i in start..end {
-            self.0[i as usize] += 1;
+    fn add(&mut self, start: Minute, end: Minute) {
+        for count in &mut self.counts[self.window.index(start)..self.window.index(end)] {
+            *count += 1;
         }
     }
 
     fn sum(&self) -> u32 {
-        self.0.iter().sum()
+        self.counts.iter().sum()
     }
 
     fn max_freq(&self) -> u32 {
-        *self.0.iter().max().unwrap()
+        *self.counts.iter().max().unwrap()
     }
 
-    fn mode(&self) -> u8 {
-        self.0
+    fn sparkline(&self) -> String {
+        const LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let max_freq = self.max_freq().max(1);
+        self.counts
             .iter()
-            .enumerate()
-            .max_by_key(|(_minute, &count)| count)
-            .map(|(minute, _)| minute as u8)
-            .unwrap()
+            .map(|&count| LEVELS[(count * 8).div_ceil(max_freq) as usize])
+            .collect()
+    }
+
+    fn modes(&self) -> Vec<Minute> {
+        let max_freq = self.max_freq();
+        self.window
+            .minutes()
+            .zip(self.counts.iter())
+            .filter(|&(_minute, &count)| count == max_freq)
+            .map(|(minute, _)| minute)
+            .collect()
+    }
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    // 1518 predates the Gregorian reform, so leap years follow the Julian rule.
+    fn is_leap_year(year: u16) -> bool {
+        year.is_multiple_of(4)
+    }
+
+    fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if Date::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    fn next(&self) -> Date {
+        if self.day < Date::days_in_month(self.year, self.month) {
+            Date {
+                day: self.day + 1,
+                ..*self
+            }
+        } else if self.month < 12 {
+            Date {
+                month: self.month + 1,
+                day: 1,
+                ..*self
+            }
+        } else {
+            Date {
+                year: self.year + 1,
+                month: 1,
+                day: 1,
+            }
+        }
+    }
+
+    fn day_number(&self) -> i32 {
+        let years = i32::from(self.year) - 1;
+        let days_before_month: i32 = (1..self.month)
+            .map(|month| i32::from(Date::days_in_month(self.year, month)))
+            .sum();
+        years * 365 + years / 4 + days_before_month + i32::from(self.day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
     }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
 struct Timestamp {
     year: u16,
     month: u8,
@@ -96,17 +754,75 @@ struct Timestamp {
     minute: u8,
 }
 
+impl Timestamp {
+    fn date(&self) -> Date {
+        Date {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+        }
+    }
+
+    fn shift_date(&self) -> Date {
+        if self.hour >= 12 {
+            self.date().next()
+        } else {
+            self.date()
+        }
+    }
+
+    fn minutes_since(&self, date: Date) -> Minute {
+        (self.date().day_number() - date.day_number()) * MINUTES_PER_DAY
+            + Minute::from(self.hour) * 60
+            + Minute::from(self.minute)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
 impl FromStr for Timestamp {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
-        Ok(Timestamp {
-            year: s[0..4].parse()?,
-            month: s[5..7].parse()?,
-            day: s[8..10].parse()?,
-            hour: s[11..13].parse()?,
-            minute: s[14..16].parse()?,
-        })
+        let bytes = s.as_bytes();
+        if bytes.len() != 16
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || bytes[10] != b' '
+            || bytes[13] != b':'
+        {
+            return Err(format!("Invalid timestamp '{}'", s));
+        }
+        let field = |range: Range<usize>| {
+            s[range]
+                .parse()
+                .map_err(|e: ParseIntError| format!("Invalid timestamp '{}': {}", s, e))
+        };
+        let timestamp = Timestamp {
+            year: field(0..4)?,
+            month: field(5..7)? as u8,
+            day: field(8..10)? as u8,
+            hour: field(11..13)? as u8,
+            minute: field(14..16)? as u8,
+        };
+        if timestamp.month < 1
+            || timestamp.month > 12
+            || timestamp.day < 1
+            || timestamp.day > Date::days_in_month(timestamp.year, timestamp.month)
+            || timestamp.hour > 23
+            || timestamp.minute > 59
+        {
+            return Err(format!("Invalid timestamp '{}': no such date or time", s));
+        }
+        Ok(timestamp)
     }
 }
 
@@ -117,13 +833,22 @@ enum Action {
 }
 
 impl FromStr for Action {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
         Ok(match s {
             "falls asleep" => Action::FallsAsleep,
             "wakes up" => Action::WakesUp,
-            _ => Action::BeginsShift(Guard(s[7..].splitn(2, ' ').next().unwrap().parse()?)),
+            _ => {
+                let id = s
+                    .strip_prefix("Guard #")
+                    .and_then(|rest| rest.strip_suffix(" begins shift"))
+                    .ok_or_else(|| format!("Unknown action '{}'", s))?;
+                Action::BeginsShift(Guard(
+                    id.parse()
+                        .map_err(|e| format!("Invalid guard id '{}': {}", id, e))?,
+                ))
+            }
         })
     }
 }
@@ -134,11 +859,15 @@ struct LogEntry {
 }
 
 impl FromStr for LogEntry {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
-        let timestamp = s[1..17].parse::<Timestamp>()?;
-        let action = s[19..].parse::<Action>()?;
+        let (timestamp, action) = s
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+            .ok_or_else(|| "Missing timestamp".to_string())?;
+        let timestamp = timestamp.parse::<Timestamp>()?;
+        let action = action.parse::<Action>()?;
         Ok(LogEntry { timestamp, action })
     }
 }
@@ -1,46 +1,608 @@
 extern crate aoc_2018;
 
-use aoc_2018::file_lines;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::io;
+use std::io::Read;
+use std::str;
+use std::str::FromStr;
+use std::time::Instant;
+
+use aoc_2018::file_lines_from;
+use aoc_2018::file_reader;
+
+const CHUNK_SIZE: usize = 64 * 1024;
 
 fn main() {
-    let polymer: String = file_lines().collect();
-    println!("Remaining units: {}", reduce(polymer.as_str()).len());
-    println!(
-        "Shortest polymer length: {}",
-        find_shortest_variant_length(polymer.as_str())
-    );
+    let argument = env::args().nth(1).expect("missing filename argument");
+    if argument == "--bench" {
+        let megabytes = env::args()
+            .nth(2)
+            .map_or(4, |size| size.parse().expect("Invalid size in megabytes"));
+        benchmark(&generate_polymer(megabytes << 20));
+        return;
+    }
+    let mut length_only = false;
+    let mut trace = false;
+    let mut removals = 1;
+    let mut rules = None;
+    for arg in env::args().skip(2) {
+        if arg == "--length-only" {
+            length_only = true;
+        } else if arg == "--trace" {
+            trace = true;
+        } else if let Some(count) = arg.strip_prefix("--remove=") {
+            removals = count
+                .parse()
+                .expect("Invalid number of unit types to remove");
+        } else if let Some(filename) = arg.strip_prefix("--rules=") {
+            rules = Some(
+                file_lines_from(filename)
+                    .collect::<Vec<String>>()
+                    .join("\n")
+                    .parse::<PairTable>()
+                    .unwrap_or_else(|error| panic!("{}", error)),
+            );
+        } else {
+            panic!("Unknown option '{}'", arg);
+        }
+    }
+    match rules {
+        None if trace => {
+            let mut polymer = Vec::new();
+            read_chunks(file_reader(), |chunk| polymer.extend_from_slice(chunk))
+                .expect("error reading file");
+            polymer.retain(|unit| !unit.is_ascii_whitespace());
+            print!("{}", explain(&polymer, &Polarity));
+        }
+        Some(ref rules) if trace => print!("{}", explain(&read_polymer(), rules)),
+        None if length_only => {
+            let length = reduced_length(file_reader(), &Polarity).expect("error reading file");
+            println!("Remaining units: {}", length);
+        }
+        None => {
+            let polymer = reduce_stream(file_reader(), &Polarity).expect("error reading file");
+            report(&polymer, &Polarity, removals);
+        }
+        Some(ref rules) if length_only => {
+            let polymer = reduce_chars(file_reader(), rules).expect("error reading file");
+            println!("Remaining units: {}", polymer.len());
+        }
+        Some(rules) => report(&read_polymer(), &rules, removals),
+    }
+}
+
+fn read_polymer() -> Vec<char> {
+    let mut polymer = String::new();
+    file_reader()
+        .read_to_string(&mut polymer)
+        .expect("error reading file");
+    polymer
+        .chars()
+        .filter(|unit| !unit.is_whitespace())
+        .collect()
+}
+
+fn report<U, R>(polymer: &[U], rules: &R, removals: usize)
+where
+    U: Copy + Ord + Into<char>,
+    R: ReactionRules<U>,
+{
+    let reduced = reduce(polymer, rules);
+    println!("Remaining units: {}", reduced.len());
+    let base = if rules.confluent() { &reduced } else { polymer };
+    let (removed, length) = find_shortest_subset_variant(base, rules, removals);
+    println!("Shortest polymer length: {}", length);
+    if removals > 1 {
+        let removed: String = removed.into_iter().map(Into::into).collect();
+        println!("Removed unit types: {}", removed);
+    }
 }
 
-fn reduce(polymer: &str) -> String {
-    let mut result = String::new();
-    for unit in polymer.chars() {
-        add_unit_with_reaction(&mut result, unit);
+trait ReactionRules<U> {
+    fn reacts(&self, a: U, b: U) -> bool;
+
+    fn unit_type(&self, unit: U) -> U;
+
+    /// Whether every order of reactions ends in the same polymer. Only then
+    /// can unit types be removed from the reduced polymer instead of the
+    /// original one.
+    fn confluent(&self) -> bool {
+        false
     }
-    result
 }
 
-fn add_unit_with_reaction(result: &mut String, unit: char) {
-    if let Some(last) = result.chars().last() {
-        if can_react(last, unit) {
-            result.pop();
+struct Polarity;
+
+impl ReactionRules<u8> for Polarity {
+    fn reacts(&self, a: u8, b: u8) -> bool {
+        a != b && a.eq_ignore_ascii_case(&b)
+    }
+
+    fn unit_type(&self, unit: u8) -> u8 {
+        unit.to_ascii_lowercase()
+    }
+
+    fn confluent(&self) -> bool {
+        true
+    }
+}
+
+struct PairTable {
+    pairs: HashSet<(char, char)>,
+    types: HashMap<char, char>,
+}
+
+impl PairTable {
+    fn add(&mut self, a: char, b: char) {
+        self.pairs.insert((a, b));
+        self.pairs.insert((b, a));
+        let (type_a, type_b) = (self.unit_type(a), self.unit_type(b));
+        let (keep, replace) = (type_a.min(type_b), type_a.max(type_b));
+        for unit_type in self.types.values_mut() {
+            if *unit_type == replace {
+                *unit_type = keep;
+            }
+        }
+        self.types.insert(a, keep);
+        self.types.insert(b, keep);
+    }
+}
+
+impl ReactionRules<char> for PairTable {
+    fn reacts(&self, a: char, b: char) -> bool {
+        self.pairs.contains(&(a, b))
+    }
+
+    fn unit_type(&self, unit: char) -> char {
+        self.types.get(&unit).cloned().unwrap_or(unit)
+    }
+}
+
+impl FromStr for PairTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let mut table = PairTable {
+            pairs: HashSet::new(),
+            types: HashMap::new(),
+        };
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            if line == "polarity" {
+                for lower in b'a'..=b'z' {
+                    table.add(char::from(lower), char::from(lower.to_ascii_uppercase()));
+                }
+                continue;
+            }
+            let units: Vec<char> = line
+                .split_whitespace()
+                .map(|unit| {
+                    let mut chars = unit.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(unit), None) => Ok(unit),
+                        _ => Err(format!("Invalid unit '{}' in rule '{}'", unit, line)),
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            match units.as_slice() {
+                &[a, b] if a != b => table.add(a, b),
+                _ => return Err(format!("Invalid rule '{}', expected two units", line)),
+            }
+        }
+        Ok(table)
+    }
+}
+
+struct Reaction {
+    left: usize,
+    right: usize,
+}
+
+struct Reducer<'r, U, R: 'r> {
+    stack: Vec<U>,
+    rules: &'r R,
+    removed: Option<U>,
+    position: usize,
+    tracing: bool,
+    positions: Vec<usize>,
+    reactions: Vec<Reaction>,
+}
+
+impl<'r, U, R> Reducer<'r, U, R>
+where
+    U: Copy + PartialEq,
+    R: ReactionRules<U>,
+{
+    fn new(rules: &'r R, removed: Option<U>) -> Self {
+        Reducer {
+            stack: Vec::new(),
+            rules,
+            removed,
+            position: 0,
+            tracing: false,
+            positions: Vec::new(),
+            reactions: Vec::new(),
+        }
+    }
+
+    fn tracing(rules: &'r R) -> Self {
+        Reducer {
+            tracing: true,
+            ..Reducer::new(rules, None)
+        }
+    }
+
+    fn push(&mut self, unit: U) {
+        let position = self.position;
+        self.position += 1;
+        if self.removed == Some(self.rules.unit_type(unit)) {
             return;
         }
+        match self.stack.last() {
+            Some(&last) if self.rules.reacts(last, unit) => {
+                self.stack.pop();
+                if self.tracing {
+                    let left = self.positions.pop().unwrap();
+                    self.reactions.push(Reaction {
+                        left,
+                        right: position,
+                    });
+                }
+            }
+            _ => {
+                self.stack.push(unit);
+                if self.tracing {
+                    self.positions.push(position);
+                }
+            }
+        }
+    }
+
+    fn extend<I: IntoIterator<Item = U>>(&mut self, units: I) {
+        for unit in units {
+            self.push(unit);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn into_units(self) -> Vec<U> {
+        self.stack
+    }
+
+    fn into_reactions(self) -> Vec<Reaction> {
+        self.reactions
+    }
+}
+
+fn read_chunks<R: Read, F: FnMut(&[u8])>(mut reader: R, mut consume: F) -> io::Result<()> {
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(count) => consume(&chunk[..count]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn reduce_reader<'r, R: Read, Rules: ReactionRules<u8>>(
+    reader: R,
+    rules: &'r Rules,
+) -> io::Result<Reducer<'r, u8, Rules>> {
+    let mut reducer = Reducer::new(rules, None);
+    read_chunks(reader, |chunk| {
+        reducer.extend(
+            chunk
+                .iter()
+                .cloned()
+                .filter(|unit| !unit.is_ascii_whitespace()),
+        )
+    })?;
+    Ok(reducer)
+}
+
+fn reduce_stream<R: Read, Rules: ReactionRules<u8>>(
+    reader: R,
+    rules: &Rules,
+) -> io::Result<Vec<u8>> {
+    reduce_reader(reader, rules).map(Reducer::into_units)
+}
+
+fn reduced_length<R: Read, Rules: ReactionRules<u8>>(
+    reader: R,
+    rules: &Rules,
+) -> io::Result<usize> {
+    reduce_reader(reader, rules).map(|reducer| reducer.len())
+}
+
+fn reduce_chars<R: Read, Rules: ReactionRules<char>>(
+    reader: R,
+    rules: &Rules,
+) -> io::Result<Vec<char>> {
+    let mut reducer = Reducer::new(rules, None);
+    let mut pending: Vec<u8> = Vec::new();
+    let mut invalid = false;
+    read_chunks(reader, |chunk| {
+        pending.extend_from_slice(chunk);
+        let valid = match str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => {
+                invalid = true;
+                pending.clear();
+                return;
+            }
+        };
+        let text = str::from_utf8(&pending[..valid]).unwrap();
+        reducer.extend(text.chars().filter(|unit| !unit.is_whitespace()));
+        pending.drain(..valid);
+    })?;
+    if invalid || !pending.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "polymer is not valid UTF-8",
+        ));
     }
-    result.push(unit);
+    Ok(reducer.into_units())
+}
+
+fn reduce<U, R>(polymer: &[U], rules: &R) -> Vec<U>
+where
+    U: Copy + PartialEq,
+    R: ReactionRules<U>,
+{
+    reduce_without(polymer, rules, None)
 }
 
-fn can_react(a: char, b: char) -> bool {
-    a != b && a.eq_ignore_ascii_case(&b)
+fn reduce_without<U, R>(polymer: &[U], rules: &R, removed: Option<U>) -> Vec<U>
+where
+    U: Copy + PartialEq,
+    R: ReactionRules<U>,
+{
+    let mut reducer = Reducer::new(rules, removed);
+    reducer.extend(polymer.iter().cloned());
+    reducer.into_units()
 }
 
-fn find_shortest_variant_length(polymer: &str) -> usize {
-    (b'a'..=b'z')
-        .map(char::from)
-        .map(|unit| reduce(remove_unit(polymer, unit).as_str()).len())
-        .min()
-        .unwrap()
+fn find_shortest_variant_length<U, R>(polymer: &[U], rules: &R) -> usize
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    if rules.confluent() {
+        shortest_variant_length(&reduce(polymer, rules), rules)
+    } else {
+        shortest_variant_length(polymer, rules)
+    }
+}
+
+fn unit_types<U, R>(polymer: &[U], rules: &R) -> Vec<U>
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    let mut types: Vec<U> = polymer.iter().map(|&unit| rules.unit_type(unit)).collect();
+    types.sort_unstable();
+    types.dedup();
+    types
 }
 
-fn remove_unit(polymer: &str, unit: char) -> String {
-    polymer.replace(|ch: char| ch.eq_ignore_ascii_case(&unit), "")
+fn variant_lengths<U, R>(polymer: &[U], rules: &R) -> Vec<(U, usize)>
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    unit_types(polymer, rules)
+        .into_iter()
+        .map(|unit_type| {
+            (
+                unit_type,
+                reduce_without(polymer, rules, Some(unit_type)).len(),
+            )
+        })
+        .collect()
+}
+
+fn shortest_variant_length<U, R>(polymer: &[U], rules: &R) -> usize
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    find_shortest_subset_variant(polymer, rules, 1).1
+}
+
+fn find_shortest_subset_variant<U, R>(polymer: &[U], rules: &R, removals: usize) -> (Vec<U>, usize)
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    let mut best = (Vec::new(), polymer.len());
+    if removals > 0 && !polymer.is_empty() {
+        best.1 = usize::MAX;
+        search_subsets(polymer, rules, None, removals, &mut Vec::new(), &mut best);
+    }
+    best
+}
+
+fn search_subsets<U, R>(
+    polymer: &[U],
+    rules: &R,
+    after: Option<U>,
+    removals: usize,
+    removed: &mut Vec<U>,
+    best: &mut (Vec<U>, usize),
+) where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    for unit_type in unit_types(polymer, rules)
+        .into_iter()
+        .filter(|&unit_type| after.is_none_or(|after| unit_type > after))
+    {
+        let variant = reduce_without(polymer, rules, Some(unit_type));
+        removed.push(unit_type);
+        if variant.len() < best.1 {
+            *best = (removed.clone(), variant.len());
+        }
+        if removals > 1
+            && !variant.is_empty()
+            && length_lower_bound(&variant, rules, unit_type, removals - 1) < best.1
+        {
+            let remaining = if rules.confluent() {
+                variant
+            } else {
+                polymer
+                    .iter()
+                    .cloned()
+                    .filter(|&unit| rules.unit_type(unit) != unit_type)
+                    .collect()
+            };
+            search_subsets(
+                &remaining,
+                rules,
+                Some(unit_type),
+                removals - 1,
+                removed,
+                best,
+            );
+        }
+        removed.pop();
+    }
+}
+
+/// Reactions remove units in pairs of the same type, so every type with an
+/// odd count leaves at least one unit behind unless it is removed later.
+fn length_lower_bound<U, R>(polymer: &[U], rules: &R, after: U, removals: usize) -> usize
+where
+    U: Copy + Ord,
+    R: ReactionRules<U>,
+{
+    let mut counts = BTreeMap::new();
+    for &unit in polymer {
+        *counts.entry(rules.unit_type(unit)).or_insert(0) += 1;
+    }
+    let (removable, fixed): (Vec<_>, Vec<_>) = counts
+        .into_iter()
+        .filter(|&(_unit_type, count)| count % 2 == 1)
+        .partition(|&(unit_type, _count)| unit_type > after);
+    fixed.len() + removable.len().saturating_sub(removals)
+}
+
+fn explain<U, R>(polymer: &[U], rules: &R) -> String
+where
+    U: Copy + Ord + Into<char>,
+    R: ReactionRules<U>,
+{
+    let mut reducer = Reducer::tracing(rules);
+    reducer.extend(polymer.iter().cloned());
+    let reactions = reducer.into_reactions();
+
+    let mut output = String::new();
+    let mut remaining: Vec<Option<char>> = polymer.iter().map(|&unit| Some(unit.into())).collect();
+    let render = |remaining: &[Option<char>]| -> String {
+        let units: String = remaining.iter().filter_map(|&unit| unit).collect();
+        format!("{:width$}", units, width = polymer.len())
+    };
+    for reaction in &reactions {
+        output.push_str(&format!(
+            "{}  '{}{}' (units {} and {}) react\n",
+            render(&remaining),
+            remaining[reaction.left].unwrap(),
+            remaining[reaction.right].unwrap(),
+            reaction.left,
+            reaction.right
+        ));
+        remaining[reaction.left] = None;
+        remaining[reaction.right] = None;
+    }
+    output.push_str(&format!(
+        "{}  No further actions can be taken.\n",
+        render(&remaining)
+    ));
+
+    let reduced = reduce(polymer, rules);
+    output.push_str(&format!("Remaining units: {}\n", reduced.len()));
+    let base = if rules.confluent() { &reduced } else { polymer };
+    for (unit_type, length) in variant_lengths(base, rules) {
+        output.push_str(&format!(
+            "Removing unit type '{}': {}\n",
+            unit_type.into(),
+            length
+        ));
+    }
+    output
+}
+
+fn generate_polymer(length: usize) -> Vec<u8> {
+    let mut state: u64 = 0x2018;
+    let mut random = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        state >> 33
+    };
+    let mut open = Vec::new();
+    let mut polymer = Vec::with_capacity(length);
+    while polymer.len() < length {
+        let choice = random() % 20;
+        if choice == 0 {
+            polymer.push(random_unit(random()));
+        } else if choice % 2 == 0 && !open.is_empty() {
+            polymer.push(open.pop().unwrap() ^ 0x20);
+        } else {
+            let unit = random_unit(random());
+            open.push(unit);
+            polymer.push(unit);
+        }
+    }
+    polymer
+}
+
+fn random_unit(random: u64) -> u8 {
+    let unit = b'a' + (random % 26) as u8;
+    if random & (1 << 20) == 0 {
+        unit
+    } else {
+        unit.to_ascii_uppercase()
+    }
+}
+
+fn benchmark(polymer: &[u8]) {
+    println!("Polymer of {} units", polymer.len());
+
+    let start = Instant::now();
+    let reduced = reduce(polymer, &Polarity);
+    println!(
+        "Reduction to {} units: {:?}",
+        reduced.len(),
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let from_original = shortest_variant_length(polymer, &Polarity);
+    let original_time = start.elapsed();
+    println!(
+        "Variant search from original polymer: {} in {:?}",
+        from_original, original_time
+    );
+
+    let start = Instant::now();
+    let from_reduced = find_shortest_variant_length(polymer, &Polarity);
+    let reduced_time = start.elapsed();
+    println!(
+        "Variant search from reduced polymer: {} in {:?} ({:.1}x faster)",
+        from_reduced,
+        reduced_time,
+        original_time.as_secs_f64() / reduced_time.as_secs_f64()
+    );
 }
@@ -2,37 +2,402 @@ extern crate aoc_2018;
 
 use aoc_2018::file_lines;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
 use std::num::ParseIntError;
 use std::str::FromStr;
 
 fn main() {
+    let mut threshold = 10000;
+    let mut compare_metrics = false;
+    let mut map = false;
+    let mut overlay = false;
+    let mut image = None;
+    for arg in env::args().skip(2) {
+        if arg == "--compare-metrics" {
+            compare_metrics = true;
+        } else if arg == "--map" {
+            map = true;
+        } else if arg == "--overlay" {
+            overlay = true;
+        } else if let Some(filename) = arg.strip_prefix("--ppm=") {
+            image = Some(filename.to_string());
+        } else {
+            threshold = arg.parse().expect("Invalid threshold");
+        }
+    }
     let sites: Vec<Point> = file_lines().map(|s| s.parse::<Point>().unwrap()).collect();
-    let area = Area::from(&sites);
+    let area = Area::from(&sites, Manhattan);
 
     println!(
         "Largest finite area: {}",
-        area.measure_finite_areas().values().max().unwrap()
+        largest(&area.measure_finite_areas())
     );
+    let region = area.safe_region(threshold);
+    println!("Total region: {}", region.size);
+    if let Some(bounds) = region.bounds {
+        println!(
+            "Region bounds: x {}..={}, y {}..={}",
+            bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y
+        );
+    }
+
+    let safe_cells = if overlay {
+        Some(area.safe_cells(threshold))
+    } else {
+        None
+    };
+    if map {
+        print!("{}", area.render_map(safe_cells.as_deref()));
+    }
+    if let Some(filename) = image {
+        let file = File::create(&filename).expect("could not create image file");
+        area.write_ppm(&mut BufWriter::new(file), safe_cells.as_deref())
+            .expect("error writing image file");
+        println!("Map written to {}", filename);
+    }
+
+    if compare_metrics {
+        compare(&Area::from(&sites, Manhattan));
+        compare(&Area::from(&sites, Chebyshev));
+        compare(&Area::from(&sites, SquaredEuclidean));
+    }
+}
+
+fn largest(finite_areas: &HashMap<&Point, u32>) -> String {
+    finite_areas
+        .values()
+        .max()
+        .map_or_else(|| "none".to_string(), u32::to_string)
+}
+
+fn compare<M: Metric + Clone>(area: &Area<M>) {
+    let infinite = area.infinite_sites(&area.owners()).len();
     println!(
-        "Total region: {}",
-        area.total_distances().filter(|&d| d < 10000).count()
+        "{}: largest finite area {}, {} of {} regions infinite",
+        area.metric.name(),
+        largest(&area.measure_finite_areas()),
+        infinite,
+        area.sites.len()
     );
 }
 
-struct Area<'a> {
-    min_x: u32,
-    max_x: u32,
-    min_y: u32,
-    max_y: u32,
+trait Metric {
+    fn name(&self) -> &'static str;
+
+    fn distance(&self, a: &Point, b: &Point) -> u64;
+
+    /// Grid steps whose breadth-first distances agree with this metric.
+    fn steps(&self) -> Option<&'static [(isize, isize)]> {
+        None
+    }
+
+    /// Sites whose region contains infinitely many points.
+    fn unbounded_sites(&self, area: &Area<Self>, owners: &[Owner]) -> HashSet<usize>
+    where
+        Self: Sized;
+}
+
+#[derive(Clone)]
+struct Manhattan;
+
+impl Metric for Manhattan {
+    fn name(&self) -> &'static str {
+        "Manhattan"
+    }
+
+    fn distance(&self, a: &Point, b: &Point) -> u64 {
+        u64::from(a.x.abs_diff(b.x)) + u64::from(a.y.abs_diff(b.y))
+    }
+
+    fn steps(&self) -> Option<&'static [(isize, isize)]> {
+        Some(&[(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    /// Clamping a point outside the bounding box onto its border shortens the
+    /// distance to every site by the same amount, so a site's region is
+    /// unbounded exactly when it owns a border point, corners included.
+    fn unbounded_sites(&self, area: &Area<Self>, owners: &[Owner]) -> HashSet<usize> {
+        area.border()
+            .filter_map(|(x, y)| owners[area.index(x, y)].site())
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn name(&self) -> &'static str {
+        "Chebyshev"
+    }
+
+    fn distance(&self, a: &Point, b: &Point) -> u64 {
+        u64::from(a.x.abs_diff(b.x).max(a.y.abs_diff(b.y)))
+    }
+
+    fn steps(&self) -> Option<&'static [(isize, isize)]> {
+        Some(&[
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ])
+    }
+
+    /// Chebyshev distance is half the Manhattan distance in the rotated frame
+    /// `u = x + y`, `v = x - y`, so the Manhattan border argument applies there.
+    /// Border points of that frame may fall between grid points, hence the
+    /// distances are compared at twice the scale.
+    fn unbounded_sites(&self, area: &Area<Self>, _owners: &[Owner]) -> HashSet<usize> {
+        let rotated: Vec<(i64, i64)> = area
+            .sites
+            .iter()
+            .map(|site| {
+                let (x, y) = (i64::from(site.x), i64::from(site.y));
+                (x + y, x - y)
+            })
+            .collect();
+        let (min_u, max_u) = min_max(rotated.iter().map(|&(u, _)| u));
+        let (min_v, max_v) = min_max(rotated.iter().map(|&(_, v)| v));
+        rectangle_border(min_u, max_u, min_v, max_v)
+            .filter_map(|(u, v)| {
+                let (x, y) = (u + v, u - v);
+                unique_nearest(area.sites, |site| {
+                    let (site_x, site_y) = (2 * i64::from(site.x), 2 * i64::from(site.y));
+                    x.abs_diff(site_x).max(y.abs_diff(site_y))
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+struct SquaredEuclidean;
+
+impl Metric for SquaredEuclidean {
+    fn name(&self) -> &'static str {
+        "Squared Euclidean"
+    }
+
+    fn distance(&self, a: &Point, b: &Point) -> u64 {
+        let x = u64::from(a.x.abs_diff(b.x));
+        let y = u64::from(a.y.abs_diff(b.y));
+        x * x + y * y
+    }
+
+    /// A Euclidean Voronoi cell is unbounded exactly when its site lies on the
+    /// boundary of the convex hull of all sites.
+    fn unbounded_sites(&self, area: &Area<Self>, _owners: &[Owner]) -> HashSet<usize> {
+        let hull = convex_hull(area.sites);
+        let on_hull = |site: &Point| {
+            hull.len() < 3
+                || (0..hull.len()).any(|i| {
+                    let (a, b) = (&hull[i], &hull[(i + 1) % hull.len()]);
+                    cross(a, b, site) == 0
+                        && (a.x.min(b.x)..=a.x.max(b.x)).contains(&site.x)
+                        && (a.y.min(b.y)..=a.y.max(b.y)).contains(&site.y)
+                })
+        };
+        area.sites
+            .iter()
+            .enumerate()
+            .filter(|&(index, site)| {
+                on_hull(site)
+                    && !area
+                        .sites
+                        .iter()
+                        .enumerate()
+                        .any(|(other, point)| other != index && point == site)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+fn cross(o: &Point, a: &Point, b: &Point) -> i64 {
+    (i64::from(a.x) - i64::from(o.x)) * (i64::from(b.y) - i64::from(o.y))
+        - (i64::from(a.y) - i64::from(o.y)) * (i64::from(b.x) - i64::from(o.x))
+}
+
+/// Vertices of the convex hull in counter-clockwise order, without collinear
+/// points.
+fn convex_hull(sites: &[Point]) -> Vec<&Point> {
+    let mut points: Vec<&Point> = sites.iter().collect();
+    points.sort_unstable_by_key(|point| (point.x, point.y));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<&Point> = Vec::new();
+    for pass in 0..2 {
+        let start = hull.len();
+        for &point in points.iter() {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+fn unique_nearest<F: Fn(&Point) -> u64>(sites: &[Point], distance: F) -> Option<usize> {
+    let mut min = u64::MAX;
+    let mut nearest = None;
+    for (index, site) in sites.iter().enumerate() {
+        let distance = distance(site);
+        if distance < min {
+            min = distance;
+            nearest = Some(index);
+        } else if distance == min {
+            nearest = None;
+        }
+    }
+    nearest
+}
+
+fn min_max<I: Iterator<Item = i64>>(values: I) -> (i64, i64) {
+    values.fold((i64::MAX, i64::MIN), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+fn rectangle_border(
+    min_x: i64,
+    max_x: i64,
+    min_y: i64,
+    max_y: i64,
+) -> impl Iterator<Item = (i64, i64)> {
+    let horizontal = (min_x..=max_x).flat_map(move |x| vec![(x, min_y), (x, max_y)]);
+    let vertical = (min_y..=max_y).flat_map(move |y| vec![(min_x, y), (max_x, y)]);
+    horizontal.chain(vertical)
+}
+
+struct SafeRegion {
+    size: u64,
+    bounds: Option<Bounds>,
+}
+
+struct Bounds {
+    min_x: i64,
+    max_x: i64,
+    min_y: i64,
+    max_y: i64,
+}
+
+struct Area<'a, M> {
+    min_x: i32,
+    max_x: i32,
+    min_y: i32,
+    max_y: i32,
     sites: &'a [Point],
+    metric: M,
 }
 
-impl<'a> Area<'a> {
-    fn from(sites: &'a [Point]) -> Self {
-        let mut min_x = u32::max_value();
-        let mut max_x = u32::min_value();
-        let mut min_y = u32::max_value();
-        let mut max_y = u32::min_value();
+impl<'a> Area<'a, Manhattan> {
+    fn safe_cells(&self, threshold: u32) -> Vec<bool> {
+        let columns = axis_distances(
+            self.sites.iter().map(|site| i64::from(site.x)),
+            i64::from(self.min_x),
+            i64::from(self.max_x),
+        );
+        let rows = axis_distances(
+            self.sites.iter().map(|site| i64::from(site.y)),
+            i64::from(self.min_y),
+            i64::from(self.max_y),
+        );
+        rows.iter()
+            .flat_map(|row| {
+                columns
+                    .iter()
+                    .map(move |column| column + row < i64::from(threshold))
+            })
+            .collect()
+    }
+
+    /// Outside the bounding box every further step adds one per site to the
+    /// total distance, so the region cannot reach further than
+    /// `threshold / sites` beyond it.
+    fn safe_region(&self, threshold: u32) -> SafeRegion {
+        if self.sites.is_empty() {
+            return SafeRegion {
+                size: 0,
+                bounds: None,
+            };
+        }
+        let threshold = i64::from(threshold);
+        let reach = threshold / self.sites.len() as i64 + 1;
+        let min_x = i64::from(self.min_x) - reach;
+        let min_y = i64::from(self.min_y) - reach;
+        let columns = axis_distances(
+            self.sites.iter().map(|site| i64::from(site.x)),
+            min_x,
+            i64::from(self.max_x) + reach,
+        );
+        let rows = axis_distances(
+            self.sites.iter().map(|site| i64::from(site.y)),
+            min_y,
+            i64::from(self.max_y) + reach,
+        );
+        let mut sorted_rows = rows.clone();
+        sorted_rows.sort_unstable();
+
+        let size = columns
+            .iter()
+            .map(|&column| sorted_rows.partition_point(|&row| column + row < threshold) as u64)
+            .sum();
+        let inside = |distances: &[i64], other_min: i64| -> Option<(i64, i64)> {
+            let first = distances.iter().position(|&d| d + other_min < threshold)?;
+            let last = distances.iter().rposition(|&d| d + other_min < threshold)?;
+            Some((first as i64, last as i64))
+        };
+        let bounds = match (
+            inside(&columns, sorted_rows[0]),
+            inside(&rows, *columns.iter().min().unwrap()),
+        ) {
+            (Some((left, right)), Some((top, bottom))) => Some(Bounds {
+                min_x: min_x + left,
+                max_x: min_x + right,
+                min_y: min_y + top,
+                max_y: min_y + bottom,
+            }),
+            _ => None,
+        };
+        SafeRegion { size, bounds }
+    }
+}
+
+impl<'a, M: Metric + Clone> Area<'a, M> {
+    fn from(sites: &'a [Point], metric: M) -> Self {
+        if sites.is_empty() {
+            return Self {
+                min_x: 0,
+                max_x: -1,
+                min_y: 0,
+                max_y: -1,
+                sites,
+                metric,
+            };
+        }
+        let mut min_x = i32::MAX;
+        let mut max_x = i32::MIN;
+        let mut min_y = i32::MAX;
+        let mut max_y = i32::MIN;
 
         for site in sites {
             min_x = min_x.min(site.x);
@@ -46,100 +411,253 @@ impl<'a> Area<'a> {
             min_y,
             max_y,
             sites,
+            metric,
         }
     }
 
-    fn total_distances(&'a self) -> impl Iterator<Item = u32> + 'a {
-        self.points()
-            .map(move |point| self.compute_total_distance(&point))
+    fn width(&self) -> usize {
+        (i64::from(self.max_x) - i64::from(self.min_x) + 1) as usize
     }
 
-    fn compute_total_distance(&self, point: &Point) -> u32 {
-        self.sites
-            .iter()
-            .map(|site| site.manhattan_distance(point))
-            .sum()
+    fn height(&self) -> usize {
+        (i64::from(self.max_y) - i64::from(self.min_y) + 1) as usize
     }
 
-    fn points(&'a self) -> impl Iterator<Item = Point> + 'a {
-        (self.min_x..=self.max_x)
-            .flat_map(move |x| (self.min_y..=self.max_y).map(move |y| Point { x, y }))
+    fn index(&self, x: i32, y: i32) -> usize {
+        self.min_y.abs_diff(y) as usize * self.width() + self.min_x.abs_diff(x) as usize
     }
 
-    fn measure_inside(&self) -> HashMap<&Point, u32> {
-        let mut finite_areas = HashMap::new();
-        for x in self.min_x + 1..self.max_x {
-            for y in self.min_y + 1..self.max_y {
-                if let Some(site) = (Point { x, y }).find_unique_nearest_site(self.sites) {
-                    let area = finite_areas.entry(site).or_insert(0);
-                    *area += 1
+    fn border(&self) -> impl Iterator<Item = (i32, i32)> {
+        rectangle_border(
+            i64::from(self.min_x),
+            i64::from(self.max_x),
+            i64::from(self.min_y),
+            i64::from(self.max_y),
+        )
+        .map(|(x, y)| (x as i32, y as i32))
+    }
+
+    fn owners(&self) -> Vec<Owner> {
+        match self.metric.steps() {
+            Some(steps) => self.flood_owners(steps),
+            None => self.scan_owners(),
+        }
+    }
+
+    fn scan_owners(&self) -> Vec<Owner> {
+        let mut owners = vec![Owner::Unreached; self.width() * self.height()];
+        for y in self.min_y..=self.max_y {
+            for x in self.min_x..=self.max_x {
+                let point = Point { x, y };
+                let mut min = u64::MAX;
+                let mut owner = Owner::Unreached;
+                for (site_index, site) in self.sites.iter().enumerate() {
+                    let distance = self.metric.distance(&point, site);
+                    if distance < min {
+                        min = distance;
+                        owner = Owner::Site(site_index);
+                    } else if distance == min {
+                        owner = Owner::Tied;
+                    }
                 }
+                owners[self.index(x, y)] = owner;
             }
         }
-        finite_areas
+        owners
+    }
+
+    fn flood_owners(&self, steps: &[(isize, isize)]) -> Vec<Owner> {
+        let (width, height) = (self.width(), self.height());
+        let mut owners = vec![Owner::Unreached; width * height];
+        let mut frontier = Vec::new();
+        for (site_index, site) in self.sites.iter().enumerate() {
+            let index = self.index(site.x, site.y);
+            owners[index] = owners[index].merge(Owner::Site(site_index));
+            frontier.push(index);
+        }
+        frontier.sort_unstable();
+        frontier.dedup();
+        while !frontier.is_empty() {
+            let mut reached = Vec::new();
+            for &index in &frontier {
+                let (x, y) = (index % width, index / width);
+                for &(dx, dy) in steps {
+                    let (x, y) = match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                        (Some(x), Some(y)) if x < width && y < height => (x, y),
+                        _ => continue,
+                    };
+                    let neighbour = y * width + x;
+                    if owners[neighbour] == Owner::Unreached {
+                        reached.push((neighbour, owners[index]));
+                    }
+                }
+            }
+            let mut next = Vec::new();
+            for (index, owner) in reached {
+                if owners[index] == Owner::Unreached {
+                    next.push(index);
+                }
+                owners[index] = owners[index].merge(owner);
+            }
+            frontier = next;
+        }
+        owners
     }
 
-    fn measure_finite_areas(&self) -> HashMap<&Point, u32> {
-        let mut finite_areas = self.measure_inside();
-        self.remove_infinite_areas(&mut finite_areas);
+    fn expanded(&self, margin: i32) -> Self {
+        Area {
+            min_x: self.min_x.saturating_sub(margin),
+            max_x: self.max_x.saturating_add(margin),
+            min_y: self.min_y.saturating_sub(margin),
+            max_y: self.max_y.saturating_add(margin),
+            sites: self.sites,
+            metric: self.metric.clone(),
+        }
+    }
+
+    /// Only Manhattan regions are guaranteed to stay inside the bounding box,
+    /// so the measured area grows until no bounded region touches its border.
+    fn measure_finite_areas(&self) -> HashMap<&'a Point, u32> {
+        let mut owners = self.owners();
+        let infinite = self.infinite_sites(&owners);
+        let finite = |owner: &Owner| owner.site().is_some_and(|site| !infinite.contains(&site));
+        let mut margin = 0;
+        let mut area = self.expanded(margin);
+        while area
+            .border()
+            .any(|(x, y)| finite(&owners[area.index(x, y)]))
+        {
+            margin = margin * 2 + 1;
+            area = self.expanded(margin);
+            owners = area.owners();
+        }
+
+        let mut finite_areas = HashMap::new();
+        for owner in owners.iter().filter(|owner| finite(owner)) {
+            let site = &self.sites[owner.site().unwrap()];
+            *finite_areas.entry(site).or_insert(0) += 1;
+        }
         finite_areas
     }
 
-    fn remove_infinite_areas(&self, finite_areas: &mut HashMap<&Point, u32>) {
-        for x in self.min_x + 1..self.max_x {
-            self.remove_nearest_site(&Point { x, y: self.min_y }, finite_areas);
-            self.remove_nearest_site(&Point { x, y: self.max_y }, finite_areas);
+    fn render_map(&self, safe_cells: Option<&[bool]>) -> String {
+        let owners = self.owners();
+        let mut labels = vec![None; owners.len()];
+        for (site_index, site) in self.sites.iter().enumerate() {
+            labels[self.index(site.x, site.y)] = Some(site_label(site_index).to_ascii_uppercase());
         }
-        for y in self.min_y + 1..self.max_y {
-            self.remove_nearest_site(&Point { x: self.min_x, y }, finite_areas);
-            self.remove_nearest_site(&Point { x: self.max_x, y }, finite_areas);
+        let mut output = String::new();
+        for (index, owner) in owners.iter().enumerate() {
+            output.push(match (labels[index], owner) {
+                (Some(label), _) => label,
+                _ if safe_cells.is_some_and(|safe| safe[index]) => '#',
+                (None, &Owner::Site(site)) => site_label(site),
+                (None, _) => '.',
+            });
+            if (index + 1) % self.width() == 0 {
+                output.push('\n');
+            }
         }
+        output
     }
 
-    fn remove_nearest_site(&self, point: &Point, finite_areas: &mut HashMap<&Point, u32>) {
-        if let Some(site) = point.find_unique_nearest_site(self.sites) {
-            finite_areas.remove(site);
+    fn write_ppm<W: Write>(&self, out: &mut W, safe_cells: Option<&[bool]>) -> io::Result<()> {
+        let owners = self.owners();
+        let mut sites = vec![false; owners.len()];
+        for site in self.sites {
+            sites[self.index(site.x, site.y)] = true;
+        }
+        writeln!(out, "P6\n{} {}\n255", self.width(), self.height())?;
+        for (index, owner) in owners.iter().enumerate() {
+            let colour = match owner {
+                _ if sites[index] => [255, 255, 255],
+                &Owner::Site(site) => site_colour(site),
+                _ => [64, 64, 64],
+            };
+            let colour = if safe_cells.is_some_and(|safe| safe[index]) {
+                colour.map(|channel| channel / 2 + 128)
+            } else {
+                colour
+            };
+            out.write_all(&colour)?;
         }
+        out.flush()
+    }
+
+    fn infinite_sites(&self, owners: &[Owner]) -> HashSet<usize> {
+        self.metric.unbounded_sites(self, owners)
     }
 }
 
-#[derive(Hash, Eq, PartialEq)]
-struct Point {
-    x: u32,
-    y: u32,
+fn site_label(site: usize) -> char {
+    char::from(b'a' + (site % 26) as u8)
 }
 
-impl Point {
-    fn manhattan_distance(&self, other: &Point) -> u32 {
-        let x_distance = if self.x > other.x {
-            self.x - other.x
-        } else {
-            other.x - self.x
-        };
-        let y_distance = if self.y > other.y {
-            self.y - other.y
-        } else {
-            other.y - self.y
-        };
-        x_distance + y_distance
+/// Spreads site hues by the golden angle so neighbouring indices differ.
+fn site_colour(site: usize) -> [u8; 3] {
+    let hue = (site as f64 * 0.618_033_988_75).fract() * 6.0;
+    let rising = (hue.fract() * 200.0) as u8 + 40;
+    let falling = 240 - (hue.fract() * 200.0) as u8;
+    match hue as u32 {
+        0 => [240, rising, 40],
+        1 => [falling, 240, 40],
+        2 => [40, 240, rising],
+        3 => [40, falling, 240],
+        4 => [rising, 40, 240],
+        _ => [240, 40, falling],
     }
+}
 
-    fn find_unique_nearest_site<'a, 'b>(&'a self, sites: &'b [Point]) -> Option<&'b Point> {
-        let mut min = u32::max_value();
-        let mut nearest = None;
-        for site in sites {
-            let distance = self.manhattan_distance(site);
-            if distance < min {
-                min = distance;
-                nearest = Some(site);
-            } else if distance == min {
-                nearest = None;
-            }
+#[derive(Clone, Copy, PartialEq)]
+enum Owner {
+    Unreached,
+    Site(usize),
+    Tied,
+}
+
+impl Owner {
+    fn site(self) -> Option<usize> {
+        match self {
+            Owner::Site(site) => Some(site),
+            _ => None,
+        }
+    }
+
+    fn merge(self, other: Owner) -> Owner {
+        match (self, other) {
+            (Owner::Unreached, owner) | (owner, Owner::Unreached) => owner,
+            (Owner::Site(a), Owner::Site(b)) if a == b => self,
+            _ => Owner::Tied,
         }
-        nearest
     }
 }
 
+/// Sum of distances from every coordinate in `min..=max` to all `values`,
+/// computed from prefix sums over the sorted values.
+fn axis_distances<I: Iterator<Item = i64>>(values: I, min: i64, max: i64) -> Vec<i64> {
+    let mut values: Vec<i64> = values.collect();
+    values.sort_unstable();
+    let total: i64 = values.iter().sum();
+    let mut below = 0;
+    let mut below_sum = 0;
+    (min..=max)
+        .map(|coordinate| {
+            while below < values.len() && values[below] < coordinate {
+                below_sum += values[below];
+                below += 1;
+            }
+            let above = (values.len() - below) as i64;
+            coordinate * below as i64 - below_sum + (total - below_sum) - coordinate * above
+        })
+        .collect()
+}
+
+#[derive(Hash, Eq, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
 impl FromStr for Point {
     type Err = ParseIntError;
 
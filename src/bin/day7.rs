@@ -1,40 +1,196 @@
 extern crate aoc_2018;
 
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
+use std::fmt;
 use std::iter::FromIterator;
+use std::process;
+use std::str::FromStr;
 
 use aoc_2018::file_lines;
+use aoc_2018::file_lines_from;
 
 fn main() {
-    let dependencies: Dependencies = file_lines().collect();
+    let dependencies = file_lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse::<Requirement>())
+        .collect::<Result<Dependencies, String>>()
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+    if let Err(error) = dependencies.check() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+
+    let mut table = None;
+    let mut reports = Vec::new();
+    for arg in env::args().skip(2) {
+        if let Some(filename) = arg.strip_prefix("--durations=") {
+            table = Some(
+                file_lines_from(filename)
+                    .collect::<Vec<String>>()
+                    .join("\n")
+                    .parse::<DurationTable>()
+                    .unwrap_or_else(|error| {
+                        eprintln!("{}", error);
+                        process::exit(1);
+                    }),
+            );
+        } else {
+            reports.push(arg);
+        }
+    }
+    let duration = |task: &TaskId| match table {
+        Some(ref table) => table.get(task),
+        None => puzzle_duration(task),
+    };
+    if let Some(task) = dependencies
+        .sorted_tasks()
+        .into_iter()
+        .find(|task| duration(task).is_none())
+    {
+        eprintln!("No duration for step '{}'", task);
+        process::exit(1);
+    }
+    let durations = |task: &TaskId| duration(task).unwrap();
+
     println!(
         "Order: {}",
-        dependencies.measure(&mut Workers::new(1, |_| 1)).tasks
+        dependencies
+            .measure(&mut Workers::new(1, &|_| 1), &Alphabetical)
+            .order()
     );
-    let Plan { tasks, time } = dependencies.measure(&mut Workers::new(5, |id| id.0 as Seconds - 4));
-    println!("Steps: {}, Order: {}", time, tasks);
+    let mut workers = Workers::new(5, &durations);
+    let plan = dependencies.measure(&mut workers, &Alphabetical);
+    let time = plan.time;
+    println!("Steps: {}, Order: {}", time, plan.order());
+
+    for report in reports {
+        let (option, value) = match report.split_once('=') {
+            Some((option, value)) => (option, Some(value)),
+            None => (report.as_str(), None),
+        };
+        match (option, value) {
+            ("--table", None) => print!("{}", workers.render_table()),
+            ("--gantt", None) => print!("{}", workers.render_gantt(GANTT_WIDTH)),
+            ("--csv", None) => print!("{}", workers.render_csv()),
+            ("--critical-path", None) => print!("{}", dependencies.critical_path(&durations)),
+            ("--dot", _) | ("--mermaid", _) => {
+                let styling = match value {
+                    None => Styling::default(),
+                    Some("order") => Styling::completion_order(&plan),
+                    Some("critical") => {
+                        Styling::critical_path(&dependencies.critical_path(&durations))
+                    }
+                    Some(other) => {
+                        eprintln!(
+                            "Unknown colouring '{}', expected 'order' or 'critical'",
+                            other
+                        );
+                        process::exit(2);
+                    }
+                };
+                if option == "--dot" {
+                    print!("{}", dependencies.to_dot(&styling));
+                } else {
+                    print!("{}", dependencies.to_mermaid(&styling));
+                }
+            }
+            ("--compare-policies", None) => {
+                let policies: [&dyn Policy; 4] = [
+                    &Alphabetical,
+                    &LongestDurationFirst(&durations),
+                    &MostDependentsFirst(&dependencies),
+                    &Fifo,
+                ];
+                for policy in policies.iter() {
+                    let plan = dependencies
+                        .measure(&mut Workers::new(workers.jobs.len(), &durations), *policy);
+                    println!(
+                        "{}: {} seconds, order {}",
+                        policy.name(),
+                        plan.time,
+                        plan.order()
+                    );
+                }
+            }
+            ("--optimal", None) => match Solver::new(&dependencies, workers.jobs.len(), &durations)
+            {
+                Ok(mut solver) => {
+                    let optimal = solver.solve(time);
+                    println!(
+                        "Optimal: {} seconds, greedy plan is {} seconds ({:.1}% over, {} nodes searched)",
+                        optimal,
+                        time,
+                        (time - optimal) as f64 * 100.0 / optimal.max(1) as f64,
+                        solver.nodes
+                    );
+                }
+                Err(error) => eprintln!("{}", error),
+            },
+            _ => {
+                eprintln!("Unknown option '{}'", report);
+                process::exit(2);
+            }
+        }
+    }
 }
 
-fn parse_line(line: &str) -> Option<(TaskId, TaskId)> {
-    let mut chars = line.chars();
-    Some((TaskId(chars.nth(5)?), TaskId(chars.nth(30)?)))
+struct Requirement {
+    before: TaskId,
+    after: TaskId,
+}
+
+impl FromStr for Requirement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let invalid = || format!("Invalid requirement '{}'", s);
+        let rest = s.trim().strip_prefix("Step ").ok_or_else(invalid)?;
+        let (before, rest) = rest
+            .split_once(" must be finished before step ")
+            .ok_or_else(invalid)?;
+        let after = rest.strip_suffix(" can begin.").ok_or_else(invalid)?;
+        Ok(Requirement {
+            before: before.parse()?,
+            after: after.parse()?,
+        })
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct TaskId(char);
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct TaskId(String);
 
-impl Ord for TaskId {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.0.cmp(&self.0)
+impl FromStr for TaskId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let name = s.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("Invalid step name '{}'", s));
+        }
+        Ok(TaskId(name.to_string()))
     }
 }
 
-impl PartialOrd for TaskId {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Joins names without a separator when every name is a single character, as
+// in the puzzle answers.
+fn join_tasks<'a, I: IntoIterator<Item = &'a TaskId>>(tasks: I) -> String {
+    let names: Vec<&str> = tasks.into_iter().map(|task| task.0.as_str()).collect();
+    if names.iter().all(|name| name.chars().count() == 1) {
+        names.concat()
+    } else {
+        names.join(",")
     }
 }
 
@@ -45,38 +201,560 @@ impl Dependencies {
         self.0.iter()
     }
 
-    fn depending(&self, task: TaskId) -> Vec<&TaskId> {
-        self.0.get(&task).map_or(vec![], |set| set.iter().collect())
+    fn sorted_tasks(&self) -> Vec<&TaskId> {
+        let mut tasks: Vec<&TaskId> = self.0.keys().collect();
+        tasks.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        tasks
+    }
+
+    fn depending(&self, task: &TaskId) -> Vec<&TaskId> {
+        self.0.get(task).map_or(vec![], |set| set.iter().collect())
     }
 
-    fn measure(&self, workers: &mut Workers) -> Plan {
-        let mut queue = Queue::from(&self);
+    fn topological_order(&self) -> Vec<TaskId> {
+        let mut queue = Queue::from(self, &Alphabetical);
+        let mut order = Vec::new();
+        while let Some(task) = queue.pop() {
+            queue.insert(self.depending(&task).as_slice());
+            order.push(task);
+        }
+        order
+    }
+
+    fn check(&self) -> Result<(), InvalidGraph> {
+        let scheduled: HashSet<TaskId> = self.topological_order().into_iter().collect();
+        if scheduled.len() == self.0.len() {
+            return Ok(());
+        }
+        let cycle = self.find_cycle().unwrap_or_default();
+        let mut unreachable: Vec<TaskId> = self
+            .0
+            .keys()
+            .filter(|task| !scheduled.contains(task) && !cycle.contains(task))
+            .cloned()
+            .collect();
+        unreachable.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Err(InvalidGraph { cycle, unreachable })
+    }
+
+    fn find_cycle(&self) -> Option<Vec<TaskId>> {
+        let mut finished = HashSet::new();
+        for task in self.sorted_tasks() {
+            let mut path = Vec::new();
+            if let Some(cycle) = self.cycle_from(task, &mut path, &mut finished) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    fn cycle_from(
+        &self,
+        task: &TaskId,
+        path: &mut Vec<TaskId>,
+        finished: &mut HashSet<TaskId>,
+    ) -> Option<Vec<TaskId>> {
+        if let Some(start) = path.iter().position(|visited| visited == task) {
+            return Some(path[start..].to_vec());
+        }
+        if finished.contains(task) {
+            return None;
+        }
+        path.push(task.clone());
+        let mut next = self.depending(task);
+        next.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        for dependent in next {
+            if let Some(cycle) = self.cycle_from(dependent, path, finished) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        finished.insert(task.clone());
+        None
+    }
+
+    fn measure(&self, workers: &mut Workers, policy: &dyn Policy) -> Plan {
+        let mut queue = Queue::from(self, policy);
         let mut result = Plan::new();
 
         while !queue.is_empty() || workers.busy() {
             workers.take_jobs(&mut queue);
 
-            if let Some((completed, elapsed)) = workers.finish_job() {
-                result.add(completed, elapsed);
-                queue.insert(self.depending(completed).as_slice());
+            if let Some((completed, elapsed)) = workers.finish_jobs() {
+                let mut elapsed = elapsed;
+                for task in completed {
+                    queue.insert(self.depending(&task).as_slice());
+                    result.add(task, elapsed);
+                    elapsed = 0;
+                }
+            }
+        }
+        result
+    }
+
+    fn critical_path(&self, durations: &Durations) -> CriticalPath {
+        let order = self.topological_order();
+        let mut earliest: HashMap<&TaskId, Seconds> = HashMap::new();
+        for task in &order {
+            let finish = *earliest.entry(task).or_insert(0) + durations(task);
+            for dependent in self.depending(task) {
+                let start = earliest.entry(dependent).or_insert(0);
+                *start = (*start).max(finish);
+            }
+        }
+        let length = order
+            .iter()
+            .map(|task| earliest[task] + durations(task))
+            .max()
+            .unwrap_or(0);
+
+        let mut latest: HashMap<&TaskId, Seconds> = HashMap::new();
+        for task in order.iter().rev() {
+            let finish = self
+                .depending(task)
+                .into_iter()
+                .map(|dependent| latest[dependent])
+                .min()
+                .unwrap_or(length);
+            latest.insert(task, finish - durations(task));
+        }
+
+        let timings: Vec<Timing> = order
+            .iter()
+            .map(|task| Timing {
+                task: task.clone(),
+                duration: durations(task),
+                earliest_start: earliest[task],
+                latest_start: latest[task],
+            })
+            .collect();
+
+        let mut chain = Vec::new();
+        let mut next = timings
+            .iter()
+            .find(|timing| timing.earliest_start == 0 && timing.slack() == 0);
+        while let Some(timing) = next {
+            chain.push(timing.task.clone());
+            let finish = timing.earliest_start + timing.duration;
+            let dependents = self.depending(&timing.task);
+            next = timings.iter().find(|other| {
+                other.slack() == 0
+                    && other.earliest_start == finish
+                    && dependents.contains(&&other.task)
+            });
+        }
+
+        CriticalPath {
+            timings,
+            chain,
+            length,
+        }
+    }
+
+    fn edges(&self) -> Vec<(&TaskId, &TaskId)> {
+        let mut edges = Vec::new();
+        for task in self.sorted_tasks() {
+            let mut dependents = self.depending(task);
+            dependents.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            edges.extend(dependents.into_iter().map(|dependent| (task, dependent)));
+        }
+        edges
+    }
+
+    fn to_dot(&self, styling: &Styling) -> String {
+        let quote =
+            |task: &TaskId| format!("\"{}\"", task.0.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut result = String::from("digraph steps {\n    rankdir=LR;\n    node [shape=box];\n");
+        for task in self.sorted_tasks() {
+            match styling.fill.get(task) {
+                Some(colour) => {
+                    result += &format!(
+                        "    {} [style=filled, fillcolor=\"{}\"];\n",
+                        quote(task),
+                        colour
+                    )
+                }
+                None => result += &format!("    {};\n", quote(task)),
+            }
+        }
+        for (before, after) in self.edges() {
+            result += &format!("    {} -> {}", quote(before), quote(after));
+            if styling.is_emphasised(before, after) {
+                result += &format!(" [color=\"{}\", penwidth=2]", EMPHASIS_COLOUR);
+            }
+            result += ";\n";
+        }
+        result += "}\n";
+        result
+    }
+
+    // Mermaid identifiers are restricted, so nodes get generated ids and the
+    // step names only appear in their labels.
+    fn to_mermaid(&self, styling: &Styling) -> String {
+        let tasks = self.sorted_tasks();
+        let ids: HashMap<&TaskId, String> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, &task)| (task, format!("n{}", i)))
+            .collect();
+        let mut result = String::from("flowchart LR\n");
+        for &task in &tasks {
+            result += &format!("    {}[\"{}\"]\n", ids[task], task.0.replace('"', "#quot;"));
+        }
+        let mut links = Vec::new();
+        for (index, (before, after)) in self.edges().into_iter().enumerate() {
+            result += &format!("    {} --> {}\n", ids[before], ids[after]);
+            if styling.is_emphasised(before, after) {
+                links.push(index);
             }
         }
+        for &task in &tasks {
+            if let Some(colour) = styling.fill.get(task) {
+                result += &format!("    style {} fill:{}\n", ids[task], colour);
+            }
+        }
+        for index in links {
+            result += &format!(
+                "    linkStyle {} stroke:{},stroke-width:2px\n",
+                index, EMPHASIS_COLOUR
+            );
+        }
         result
     }
 }
 
+#[derive(Default)]
+struct Styling {
+    fill: HashMap<TaskId, String>,
+    emphasised: HashSet<(TaskId, TaskId)>,
+}
+
+impl Styling {
+    // Shades steps from light to dark blue in the order they were completed.
+    fn completion_order(plan: &Plan) -> Self {
+        let (first, last) = ([222.0, 235.0, 247.0], [49.0, 130.0, 189.0]);
+        let steps = plan.tasks.len().saturating_sub(1).max(1) as f64;
+        let fill = plan
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(position, task)| {
+                let t = position as f64 / steps;
+                let channel = |i: usize| (first[i] + (last[i] - first[i]) * t).round() as u8;
+                let colour = format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2));
+                (task.clone(), colour)
+            })
+            .collect();
+        Styling {
+            fill,
+            emphasised: HashSet::new(),
+        }
+    }
+
+    fn critical_path(critical_path: &CriticalPath) -> Self {
+        let fill = critical_path
+            .timings
+            .iter()
+            .filter(|timing| timing.slack() == 0)
+            .map(|timing| (timing.task.clone(), String::from(CRITICAL_COLOUR)))
+            .collect();
+        let emphasised = critical_path
+            .chain
+            .windows(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Styling { fill, emphasised }
+    }
+
+    fn is_emphasised(&self, before: &TaskId, after: &TaskId) -> bool {
+        self.emphasised.contains(&(before.clone(), after.clone()))
+    }
+}
+
+const CRITICAL_COLOUR: &str = "#f4a6a6";
+const EMPHASIS_COLOUR: &str = "#d62728";
+
+struct Timing {
+    task: TaskId,
+    duration: Seconds,
+    earliest_start: Seconds,
+    latest_start: Seconds,
+}
+
+impl Timing {
+    fn slack(&self) -> Seconds {
+        self.latest_start - self.earliest_start
+    }
+}
+
+struct CriticalPath {
+    timings: Vec<Timing>,
+    chain: Vec<TaskId>,
+    length: Seconds,
+}
+
+impl fmt::Display for CriticalPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .timings
+            .iter()
+            .map(|timing| timing.task.0.len())
+            .fold(4, usize::max);
+        writeln!(
+            f,
+            "{:<width$}  Duration  Earliest  Latest  Slack",
+            "Task",
+            width = width
+        )?;
+        for timing in &self.timings {
+            writeln!(
+                f,
+                "{:<width$}  {:>8}  {:>8}  {:>6}  {:>5}",
+                timing.task.0,
+                timing.duration,
+                timing.earliest_start,
+                timing.latest_start,
+                timing.slack(),
+                width = width
+            )?;
+        }
+        let chain: Vec<String> = self.chain.iter().map(TaskId::to_string).collect();
+        writeln!(f, "Critical chain: {}", chain.join(" -> "))?;
+        writeln!(f, "Lower bound with unlimited workers: {}", self.length)
+    }
+}
+
+const MAX_EXACT_TASKS: usize = 16;
+
+// Branch and bound over start decisions. At each point in time the search
+// either starts another ready task on a free worker or waits for the next
+// running task to finish, which also covers schedules that deliberately leave
+// a worker idle.
+struct Solver {
+    durations: Vec<Seconds>,
+    tails: Vec<Seconds>,
+    prerequisites: Vec<u64>,
+    workers: usize,
+    best: Seconds,
+    nodes: usize,
+}
+
+impl Solver {
+    fn new(
+        dependencies: &Dependencies,
+        workers: usize,
+        durations: &Durations,
+    ) -> Result<Self, String> {
+        let critical_path = dependencies.critical_path(durations);
+        let timings = &critical_path.timings;
+        if timings.len() > MAX_EXACT_TASKS {
+            return Err(format!(
+                "Too many tasks for the exact solver: {} (at most {})",
+                timings.len(),
+                MAX_EXACT_TASKS
+            ));
+        }
+        let index: HashMap<&TaskId, usize> = timings
+            .iter()
+            .enumerate()
+            .map(|(i, timing)| (&timing.task, i))
+            .collect();
+        let mut prerequisites = vec![0; timings.len()];
+        for (i, timing) in timings.iter().enumerate() {
+            for dependent in dependencies.depending(&timing.task) {
+                prerequisites[index[dependent]] |= 1 << i;
+            }
+        }
+        Ok(Solver {
+            durations: timings.iter().map(|timing| timing.duration).collect(),
+            tails: timings
+                .iter()
+                .map(|timing| critical_path.length - timing.latest_start)
+                .collect(),
+            prerequisites,
+            workers: workers.max(1),
+            best: Seconds::MAX,
+            nodes: 0,
+        })
+    }
+
+    fn solve(&mut self, upper_bound: Seconds) -> Seconds {
+        self.best = upper_bound.saturating_add(1);
+        self.nodes = 0;
+        self.search(0, 0, &mut Vec::new(), 0);
+        self.best.min(upper_bound)
+    }
+
+    fn search(
+        &mut self,
+        time: Seconds,
+        finished: u64,
+        running: &mut Vec<(usize, Seconds)>,
+        first: usize,
+    ) {
+        self.nodes += 1;
+        let all = (1u64 << self.durations.len()) - 1;
+        if finished == all {
+            self.best = self.best.min(time);
+            return;
+        }
+        if self.lower_bound(time, finished, running) >= self.best {
+            return;
+        }
+
+        if running.len() < self.workers {
+            let started = running
+                .iter()
+                .fold(finished, |mask, &(task, _)| mask | 1 << task);
+            for task in first..self.durations.len() {
+                let ready = started & 1 << task == 0 && self.prerequisites[task] & !finished == 0;
+                if ready {
+                    running.push((task, time + self.durations[task]));
+                    self.search(time, finished, running, task + 1);
+                    running.pop();
+                }
+            }
+        }
+
+        if let Some(next) = running.iter().map(|&(_, finish)| finish).min() {
+            let (done, mut still_running): (Vec<_>, Vec<_>) =
+                running.iter().partition(|&&(_, finish)| finish == next);
+            let finished = done
+                .iter()
+                .fold(finished, |mask, &(task, _)| mask | 1 << task);
+            self.search(next, finished, &mut still_running, 0);
+        }
+    }
+
+    fn lower_bound(&self, time: Seconds, finished: u64, running: &[(usize, Seconds)]) -> Seconds {
+        let started = running
+            .iter()
+            .fold(finished, |mask, &(task, _)| mask | 1 << task);
+        let mut bound = running
+            .iter()
+            .map(|&(_, finish)| finish)
+            .max()
+            .unwrap_or(time);
+        let mut work: Seconds = running.iter().map(|&(_, finish)| finish - time).sum();
+        for task in (0..self.durations.len()).filter(|&task| started & 1 << task == 0) {
+            let start = running
+                .iter()
+                .filter(|&&(other, _)| self.prerequisites[task] & 1 << other != 0)
+                .map(|&(_, finish)| finish)
+                .fold(time, Seconds::max);
+            bound = bound.max(start + self.tails[task]);
+            work += self.durations[task];
+        }
+        bound.max(time + work.div_ceil(self.workers))
+    }
+}
+
+struct InvalidGraph {
+    cycle: Vec<TaskId>,
+    unreachable: Vec<TaskId>,
+}
+
+impl fmt::Display for InvalidGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |tasks: &[TaskId]| -> String {
+            tasks
+                .iter()
+                .map(TaskId::to_string)
+                .collect::<Vec<String>>()
+                .join(" -> ")
+        };
+        write!(
+            f,
+            "Cyclic dependencies: {} -> {}",
+            join(&self.cycle),
+            self.cycle[0]
+        )?;
+        if !self.unreachable.is_empty() {
+            let unreachable: Vec<String> = self.unreachable.iter().map(TaskId::to_string).collect();
+            write!(f, "\nNever reachable: {}", unreachable.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 type Seconds = usize;
 
-struct Workers {
+type Durations<'a> = dyn Fn(&TaskId) -> Seconds + 'a;
+
+const BASE_DURATION: Seconds = 60;
+
+fn puzzle_duration(task: &TaskId) -> Option<Seconds> {
+    let mut chars = task.0.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter @ 'A'..='Z'), None) => {
+            Some(BASE_DURATION + (letter as Seconds - 'A' as Seconds) + 1)
+        }
+        _ => None,
+    }
+}
+
+struct DurationTable(HashMap<TaskId, Seconds>);
+
+impl DurationTable {
+    fn get(&self, task: &TaskId) -> Option<Seconds> {
+        self.0.get(task).cloned()
+    }
+}
+
+impl FromStr for DurationTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let mut table = HashMap::new();
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (task, seconds) = match fields.as_slice() {
+                &[task, seconds] => (task, seconds),
+                _ => {
+                    return Err(format!(
+                        "Invalid duration '{}', expected a step and seconds",
+                        line
+                    ))
+                }
+            };
+            let seconds = seconds
+                .parse()
+                .map_err(|e| format!("Invalid seconds '{}' for step '{}': {}", seconds, task, e))?;
+            if table.insert(task.parse()?, seconds).is_some() {
+                return Err(format!("Duplicate duration for step '{}'", task));
+            }
+        }
+        Ok(DurationTable(table))
+    }
+}
+
+const GANTT_WIDTH: usize = 80;
+
+struct Assignment {
+    worker: usize,
+    task: TaskId,
+    start: Seconds,
+    finish: Seconds,
+}
+
+struct Workers<'a> {
     jobs: Vec<Option<(TaskId, Seconds)>>,
-    durations: fn(TaskId) -> Seconds,
+    durations: &'a Durations<'a>,
+    now: Seconds,
+    schedule: Vec<Assignment>,
 }
 
-impl Workers {
-    fn new(count: usize, durations: fn(TaskId) -> Seconds) -> Self {
+impl<'a> Workers<'a> {
+    fn new(count: usize, durations: &'a Durations<'a>) -> Self {
         Workers {
             jobs: vec![None; count],
             durations,
+            now: 0,
+            schedule: Vec::new(),
         }
     }
 
@@ -85,74 +763,235 @@ impl Workers {
     }
 
     fn take_jobs(&mut self, queue: &mut Queue) {
-        for mut worker in self.jobs.iter_mut().filter(|v| v.is_none()) {
+        for (worker, job) in self.jobs.iter_mut().enumerate() {
+            if job.is_some() {
+                continue;
+            }
             if let Some(task) = queue.pop() {
-                worker.get_or_insert((task, (self.durations)(task)));
+                let duration = (self.durations)(&task);
+                self.schedule.push(Assignment {
+                    worker,
+                    task: task.clone(),
+                    start: self.now,
+                    finish: self.now + duration,
+                });
+                *job = Some((task, duration));
             }
         }
     }
 
-    fn finish_job(&mut self) -> Option<(TaskId, Seconds)> {
-        let job = self
+    fn finish_jobs(&mut self) -> Option<(Vec<TaskId>, Seconds)> {
+        let elapsed = self
             .jobs
             .iter()
-            .filter_map(|v| *v)
-            .min_by_key(|(_, remaining)| *remaining)
-            .take();
-        if let Some((_, elapsed)) = job {
-            for mut worker in self.jobs.iter_mut() {
-                if let Some((task, time)) = worker.take() {
-                    let remaining = time - elapsed;
-                    if remaining > 0 {
-                        worker.replace((task, remaining));
-                    }
+            .flatten()
+            .map(|&(_, remaining)| remaining)
+            .min()?;
+        let mut completed = Vec::new();
+        for job in self.jobs.iter_mut() {
+            if let Some((_, remaining)) = job {
+                *remaining -= elapsed;
+                if *remaining == 0 {
+                    completed.extend(job.take().map(|(task, _)| task));
                 }
             }
         }
-        job
+        self.now += elapsed;
+        Some((completed, elapsed))
+    }
+
+    fn render_table(&self) -> String {
+        let mut completed: Vec<&Assignment> = self.schedule.iter().collect();
+        completed.sort_by_key(|assignment| assignment.finish);
+
+        let width = self
+            .schedule
+            .iter()
+            .map(|assignment| assignment.task.0.len())
+            .fold(format!("Worker {}", self.jobs.len()).len(), usize::max);
+        let mut result = String::from("Second");
+        for worker in 1..=self.jobs.len() {
+            let header = format!("Worker {}", worker);
+            result += &format!("   {:^width$}", header, width = width);
+        }
+        result += "   Done\n";
+        for second in 0..=self.now {
+            let done = join_tasks(
+                completed
+                    .iter()
+                    .filter(|a| a.finish <= second)
+                    .map(|a| &a.task),
+            );
+            result += &format!("{:>6}", second);
+            for worker in 0..self.jobs.len() {
+                let task = self
+                    .running(worker, second)
+                    .map_or(".", |a| a.task.0.as_str());
+                result += &format!("   {:^width$}", task, width = width);
+            }
+            result += &format!("   {}\n", done);
+        }
+        result
+    }
+
+    fn render_gantt(&self, width: usize) -> String {
+        let scale = self.now.max(1).div_ceil(width);
+        let mut result = String::new();
+        for worker in 0..self.jobs.len() {
+            let bar: String = (0..self.now)
+                .step_by(scale)
+                .map(|second| {
+                    self.running(worker, second)
+                        .and_then(|a| a.task.0.chars().next())
+                        .unwrap_or('.')
+                })
+                .collect();
+            result += &format!("Worker {} |{}|\n", worker + 1, bar);
+        }
+        result += &format!(
+            "{} second(s) per column, {} seconds total\n",
+            scale, self.now
+        );
+        result
+    }
+
+    fn render_csv(&self) -> String {
+        let mut result = String::from("worker,task,start,finish\n");
+        for assignment in &self.schedule {
+            result += &format!(
+                "{},{},{},{}\n",
+                assignment.worker + 1,
+                assignment.task,
+                assignment.start,
+                assignment.finish
+            );
+        }
+        result
+    }
+
+    fn running(&self, worker: usize, second: Seconds) -> Option<&Assignment> {
+        self.schedule.iter().find(|assignment| {
+            assignment.worker == worker && assignment.start <= second && second < assignment.finish
+        })
     }
 }
 
 struct Plan {
-    tasks: String,
+    tasks: Vec<TaskId>,
     time: Seconds,
 }
 
 impl Plan {
     fn new() -> Self {
         Plan {
-            tasks: String::new(),
+            tasks: Vec::new(),
             time: 0,
         }
     }
 
     fn add(&mut self, task: TaskId, time: Seconds) {
-        self.tasks.push(task.0);
+        self.tasks.push(task);
         self.time += time;
     }
+
+    fn order(&self) -> String {
+        join_tasks(&self.tasks)
+    }
+}
+
+trait Policy {
+    fn name(&self) -> &'static str;
+
+    // `Ordering::Less` means `a` is started before `b`.
+    fn compare(&self, a: &Ready, b: &Ready) -> Ordering;
 }
 
-struct Queue {
+struct Alphabetical;
+
+impl Policy for Alphabetical {
+    fn name(&self) -> &'static str {
+        "Alphabetical"
+    }
+
+    fn compare(&self, a: &Ready, b: &Ready) -> Ordering {
+        a.task.0.cmp(&b.task.0)
+    }
+}
+
+struct LongestDurationFirst<'a>(&'a Durations<'a>);
+
+impl<'a> Policy for LongestDurationFirst<'a> {
+    fn name(&self) -> &'static str {
+        "Longest duration first"
+    }
+
+    fn compare(&self, a: &Ready, b: &Ready) -> Ordering {
+        (self.0)(&b.task)
+            .cmp(&(self.0)(&a.task))
+            .then_with(|| Alphabetical.compare(a, b))
+    }
+}
+
+struct MostDependentsFirst<'a>(&'a Dependencies);
+
+impl<'a> Policy for MostDependentsFirst<'a> {
+    fn name(&self) -> &'static str {
+        "Most dependents first"
+    }
+
+    fn compare(&self, a: &Ready, b: &Ready) -> Ordering {
+        let dependents = |ready: &Ready| self.0.depending(&ready.task).len();
+        dependents(b)
+            .cmp(&dependents(a))
+            .then_with(|| Alphabetical.compare(a, b))
+    }
+}
+
+struct Fifo;
+
+impl Policy for Fifo {
+    fn name(&self) -> &'static str {
+        "First in, first out"
+    }
+
+    fn compare(&self, a: &Ready, b: &Ready) -> Ordering {
+        a.sequence.cmp(&b.sequence)
+    }
+}
+
+struct Ready {
+    task: TaskId,
+    sequence: usize,
+}
+
+struct Queue<'p> {
     required: HashMap<TaskId, usize>,
-    available: BinaryHeap<TaskId>,
+    available: Vec<Ready>,
+    policy: &'p dyn Policy,
+    sequence: usize,
 }
 
-impl Queue {
-    fn from(dependencies: &Dependencies) -> Self {
+impl<'p> Queue<'p> {
+    fn from(dependencies: &Dependencies, policy: &'p dyn Policy) -> Self {
         let mut required = HashMap::new();
-        for &task in dependencies.tasks().flat_map(|(_key, value)| value.iter()) {
-            let mut count = required.entry(task).or_insert(0);
+        for task in dependencies.tasks().flat_map(|(_key, value)| value.iter()) {
+            let count = required.entry(task.clone()).or_insert(0);
             *count += 1;
         }
-        let available = dependencies
-            .tasks()
-            .map(|(task, _)| *task)
+        let mut queue = Queue {
+            required: HashMap::new(),
+            available: Vec::new(),
+            policy,
+            sequence: 0,
+        };
+        let sources: Vec<&TaskId> = dependencies
+            .sorted_tasks()
+            .into_iter()
             .filter(|task| !required.contains_key(task))
             .collect();
-        Queue {
-            required,
-            available,
-        }
+        queue.push_all(&sources);
+        queue.required = required;
+        queue
     }
 
     fn is_empty(&self) -> bool {
@@ -160,32 +999,52 @@ impl Queue {
     }
 
     fn pop(&mut self) -> Option<TaskId> {
-        self.available.pop()
+        let policy = self.policy;
+        let (index, _) = self
+            .available
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| policy.compare(a, b))?;
+        Some(self.available.swap_remove(index).task)
     }
 
     fn insert(&mut self, tasks: &[&TaskId]) {
+        let mut ready = Vec::new();
         for &task in tasks {
-            if let Some(mut count) = self.required.get_mut(task) {
+            if let Some(count) = self.required.get_mut(task) {
                 *count -= 1;
                 if *count == 0 {
-                    self.available.push(*task);
+                    ready.push(task);
                 }
             }
         }
+        self.push_all(&ready);
+    }
+
+    // Tasks becoming ready at the same moment are queued alphabetically so
+    // that FIFO ordering does not depend on hash iteration order.
+    fn push_all(&mut self, tasks: &[&TaskId]) {
+        let mut tasks = tasks.to_vec();
+        tasks.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        for task in tasks {
+            self.available.push(Ready {
+                task: task.clone(),
+                sequence: self.sequence,
+            });
+            self.sequence += 1;
+        }
     }
 }
 
-impl<I> FromIterator<I> for Dependencies
-where
-    I: AsRef<str>,
-{
-    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+impl FromIterator<Requirement> for Dependencies {
+    fn from_iter<T: IntoIterator<Item = Requirement>>(iter: T) -> Self {
         let mut result = HashMap::new();
-        for line in iter {
-            if let Some((r, d)) = parse_line(line.as_ref()) {
-                result.entry(r).or_insert_with(HashSet::new).insert(d);
-                result.entry(d).or_insert_with(HashSet::new);
-            }
+        for Requirement { before, after } in iter {
+            result.entry(after.clone()).or_insert_with(HashSet::new);
+            result
+                .entry(before)
+                .or_insert_with(HashSet::new)
+                .insert(after);
         }
         Dependencies(result)
     }
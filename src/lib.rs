@@ -2,6 +2,7 @@ use std::env;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Read;
 
 pub fn file_lines() -> impl Iterator<Item = String> {
     let filename = env::args().nth(1).expect("missing filename argument");
@@ -14,3 +15,12 @@ pub fn file_lines_from(filename: &str) -> impl Iterator<Item = String> {
         .lines()
         .map(|line| line.expect("error reading file"))
 }
+
+pub fn file_reader() -> impl Read {
+    let filename = env::args().nth(1).expect("missing filename argument");
+    file_reader_from(filename.as_str())
+}
+
+pub fn file_reader_from(filename: &str) -> impl Read {
+    File::open(filename).expect("could not open file")
+}